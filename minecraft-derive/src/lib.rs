@@ -0,0 +1,266 @@
+//! Derive macros for the packets of the `minecraft` crate.
+//!
+//! `#[derive(Packet)]` registers a packet of the handshake, status or login
+//! state with its ID, given through one or more attributes like
+//! `#[packet(id = 0x00, state = Login, direction = Serverbound)]`. A packet
+//! sent in both directions, like Ping, carries one attribute per direction.
+//!
+//! The codec builds its tables of these states from the registrations, and
+//! deriving `Packet` for a packet missing from them fails to compile.
+//! Registering two packets with the same ID in the same state and direction
+//! fails to compile as well, with conflicting implementations of
+//! `Registered`:
+//!
+//! ```compile_fail,E0119
+//! # mod net {
+//! #     pub mod connection {
+//! #         #[derive(Eq, PartialEq)]
+//! #         pub enum ConnectionState { Start, Status, Login, Play }
+//! #     }
+//! #     pub mod packets {
+//! #         pub enum Serverbound {}
+//! #         pub trait Listed<D> {}
+//! #         pub trait Packet<D>: Listed<D> {
+//! #             const STATE: super::connection::ConnectionState;
+//! #             const ID: i32;
+//! #         }
+//! #         pub struct Slot<S, D, I>(std::marker::PhantomData<(S, D, I)>);
+//! #         pub trait Registered {}
+//! #         impl Listed<Serverbound> for crate::LoginStart {}
+//! #         impl Listed<Serverbound> for crate::OtherLoginStart {}
+//! #     }
+//! # }
+//! use minecraft_derive::Packet;
+//!
+//! #[derive(Packet)]
+//! #[packet(id = 0x00, state = Login, direction = Serverbound)]
+//! struct LoginStart;
+//!
+//! #[derive(Packet)]
+//! #[packet(id = 0x00, state = Login, direction = Serverbound)]
+//! struct OtherLoginStart;
+//! # fn main() {}
+//! ```
+//!
+//! Whereas distinct IDs are fine:
+//!
+//! ```
+//! # mod net {
+//! #     pub mod connection {
+//! #         #[derive(Eq, PartialEq)]
+//! #         pub enum ConnectionState { Start, Status, Login, Play }
+//! #     }
+//! #     pub mod packets {
+//! #         pub enum Serverbound {}
+//! #         pub trait Listed<D> {}
+//! #         pub trait Packet<D>: Listed<D> {
+//! #             const STATE: super::connection::ConnectionState;
+//! #             const ID: i32;
+//! #         }
+//! #         pub struct Slot<S, D, I>(std::marker::PhantomData<(S, D, I)>);
+//! #         pub trait Registered {}
+//! #         impl Listed<Serverbound> for crate::LoginStart {}
+//! #         impl Listed<Serverbound> for crate::OtherLoginStart {}
+//! #     }
+//! # }
+//! use minecraft_derive::Packet;
+//!
+//! #[derive(Packet)]
+//! #[packet(id = 0x00, state = Login, direction = Serverbound)]
+//! struct LoginStart;
+//!
+//! #[derive(Packet)]
+//! #[packet(id = 0x01, state = Login, direction = Serverbound)]
+//! struct OtherLoginStart;
+//! # fn main() {}
+//! ```
+//!
+//! Negative IDs are rejected:
+//!
+//! ```compile_fail
+//! # mod net {
+//! #     pub mod connection {
+//! #         #[derive(Eq, PartialEq)]
+//! #         pub enum ConnectionState { Start, Status, Login, Play }
+//! #     }
+//! #     pub mod packets {
+//! #         pub enum Serverbound {}
+//! #         pub trait Listed<D> {}
+//! #         pub trait Packet<D>: Listed<D> {
+//! #             const STATE: super::connection::ConnectionState;
+//! #             const ID: i32;
+//! #         }
+//! #         pub struct Slot<S, D, I>(std::marker::PhantomData<(S, D, I)>);
+//! #         pub trait Registered {}
+//! #         impl Listed<Serverbound> for crate::LoginStart {}
+//! #     }
+//! # }
+//! use minecraft_derive::Packet;
+//!
+//! #[derive(Packet)]
+//! #[packet(id = -1, state = Login, direction = Serverbound)]
+//! struct LoginStart;
+//! # fn main() {}
+//! ```
+//!
+//! Packets of the play state cannot be derived, since their IDs depend on the
+//! protocol version. They are mapped by the tables of the play module.
+//!
+//! The generated code refers to the `minecraft` crate through `crate::`, so
+//! the macro can only be used inside of it. The examples above stand in for
+//! it with a mock of the items the generated code uses.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, Attribute, DeriveInput, Error, Ident, LitInt, Result, Token,
+};
+
+#[proc_macro_derive(Packet, attributes(packet))]
+pub fn derive_packet(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// The contents of a `#[packet(...)]` attribute.
+struct Registration {
+    id: LitInt,
+    state: Ident,
+    direction: Ident,
+}
+
+impl Registration {
+    fn from_attr(attr: &Attribute) -> Result<Self> {
+        let registration = attr.parse_args::<Registration>()?;
+
+        match registration.state.to_string().as_str() {
+            "Start" | "Status" | "Login" => {}
+            "Play" => {
+                return Err(Error::new(
+                    registration.state.span(),
+                    "play packets have different IDs per protocol version, \
+                     map them in the tables of the play module instead",
+                ));
+            }
+            _ => {
+                return Err(Error::new(
+                    registration.state.span(),
+                    "expected one of `Start`, `Status` or `Login`",
+                ));
+            }
+        }
+
+        match registration.direction.to_string().as_str() {
+            "Serverbound" | "Clientbound" => Ok(registration),
+            _ => Err(Error::new(
+                registration.direction.span(),
+                "expected `Serverbound` or `Clientbound`",
+            )),
+        }
+    }
+}
+
+impl Parse for Registration {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut id = None;
+        let mut state = None;
+        let mut direction = None;
+
+        while !input.is_empty() {
+            let key = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+
+            match key.to_string().as_str() {
+                "id" if id.is_none() => {
+                    id = Some(input.parse()?);
+                }
+                "state" if state.is_none() => state = Some(input.parse()?),
+                "direction" if direction.is_none() => {
+                    direction = Some(input.parse()?);
+                }
+                "id" | "state" | "direction" => {
+                    return Err(Error::new(key.span(), "duplicate key"));
+                }
+                _ => {
+                    return Err(Error::new(
+                        key.span(),
+                        "expected `id`, `state` or `direction`",
+                    ));
+                }
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        let missing =
+            |key| Error::new(Span::call_site(), format!("missing `{}`", key));
+        Ok(Registration {
+            id: id.ok_or_else(|| missing("id"))?,
+            state: state.ok_or_else(|| missing("state"))?,
+            direction: direction.ok_or_else(|| missing("direction"))?,
+        })
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let registrations = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("packet"))
+        .map(Registration::from_attr)
+        .collect::<Result<Vec<_>>>()?;
+    if registrations.is_empty() {
+        return Err(Error::new(
+            input.ident.span(),
+            "missing `#[packet(id = ..., state = ..., direction = ...)]`",
+        ));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let impls = registrations.iter().map(|registration| {
+        let Registration {
+            id,
+            state,
+            direction,
+        } = registration;
+
+        // The ID doubles as the length of an array in the slot of the packet.
+        let id_value = id.base10_parse::<i32>()?;
+        if id_value < 0 {
+            return Err(Error::new_spanned(id, "packet id must be non-negative"));
+        }
+        let index = Literal::usize_unsuffixed(id_value as usize);
+
+        Ok(quote! {
+            impl #impl_generics crate::net::packets::Packet<crate::net::packets::#direction>
+                for #name #ty_generics #where_clause
+            {
+                const STATE: crate::net::connection::ConnectionState =
+                    crate::net::connection::ConnectionState::#state;
+                const ID: i32 = #id_value;
+            }
+
+            impl crate::net::packets::Registered
+                for crate::net::packets::Slot<
+                    [(); crate::net::connection::ConnectionState::#state as usize],
+                    crate::net::packets::#direction,
+                    [(); #index],
+                >
+            {
+            }
+        })
+    }).collect::<Result<Vec<_>>>()?;
+
+    Ok(quote! { #(#impls)* })
+}
@@ -1,11 +1,16 @@
 use bytes::{Buf, BufMut};
-use mc_varint::{VarIntRead, VarIntWrite, VarLongRead, VarLongWrite};
 use std::{
     i32,
-    io::{ErrorKind, Read, Result},
+    io::{Error, ErrorKind, Read, Result},
     usize,
 };
 
+/// The maximum number of bytes of a VarInt.
+pub const MAX_VAR_I32_LENGTH: usize = 5;
+
+/// The maximum number of bytes of a VarLong.
+pub const MAX_VAR_I64_LENGTH: usize = 10;
+
 pub trait VarReadExt {
     fn read_var_i32(&mut self) -> Result<i32>;
 
@@ -40,11 +45,11 @@ pub trait VarWriteExt {
 
 impl<B: Buf> VarReadExt for B {
     fn read_var_i32(&mut self) -> Result<i32> {
-        Ok(self.by_ref().reader().read_var_int()?.into())
+        Ok(read_var_u64(self, MAX_VAR_I32_LENGTH)? as i32)
     }
 
     fn read_var_i64(&mut self) -> Result<i64> {
-        Ok(self.by_ref().reader().read_var_long()?.into())
+        Ok(read_var_u64(self, MAX_VAR_I64_LENGTH)? as i64)
     }
 
     fn read_str(&mut self) -> Result<String> {
@@ -69,7 +74,9 @@ impl<B: BufMut> VarWriteExt for B {
             return Err(ErrorKind::UnexpectedEof.into());
         }
 
-        self.by_ref().writer().write_var_int(val.into())
+        // Prevent sign-extension during shift
+        write_var_u64(self, u64::from(val as u32));
+        Ok(())
     }
 
     fn write_var_i64(&mut self, val: i64) -> Result<()> {
@@ -77,7 +84,8 @@ impl<B: BufMut> VarWriteExt for B {
             return Err(ErrorKind::UnexpectedEof.into());
         }
 
-        self.by_ref().writer().write_var_long(val.into())
+        write_var_u64(self, val as u64);
+        Ok(())
     }
 
     fn write_str(&mut self, data: &str) -> Result<()> {
@@ -91,6 +99,37 @@ impl<B: BufMut> VarWriteExt for B {
     }
 }
 
+/// Reads a variable-length integer of at most `max_len` bytes.
+///
+/// Returns an error of kind `InvalidData` if the integer is longer, and of
+/// kind `UnexpectedEof` if the buffer ends before the integer.
+fn read_var_u64<B: Buf + ?Sized>(buf: &mut B, max_len: usize) -> Result<u64> {
+    let mut val = 0;
+
+    for i in 0..max_len {
+        if !buf.has_remaining() {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+
+        let byte = buf.get_u8();
+        val |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(val);
+        }
+    }
+
+    Err(Error::new(ErrorKind::InvalidData, "VarInt too long"))
+}
+
+fn write_var_u64<B: BufMut + ?Sized>(buf: &mut B, mut val: u64) {
+    while val >= 0x80 {
+        buf.put_u8((val as u8 & 0x7f) | 0x80);
+        val >>= 7;
+    }
+
+    buf.put_u8(val as u8);
+}
+
 #[inline]
 pub fn var_i32_length(val: i32) -> usize {
     // Prevent sign-extension during shift
@@ -167,6 +206,33 @@ mod tests {
         assert_eq!(cur.read_var_i32().unwrap(), -1);
     }
 
+    #[test]
+    fn var_read_invalid() {
+        let mut cur = Cursor::new(&[0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+        let err = cur.read_var_i32().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let mut cur = Cursor::new(&[0xff; 11]);
+        let err = cur.read_var_i64().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let mut cur = Cursor::new(&[0x80, 0x80]);
+        let err = cur.read_var_i32().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn var_i64_round_trip() {
+        let mut data = Vec::new();
+
+        for &val in &[0, 1, -1, i64::min_value(), i64::max_value()] {
+            data.write_var_i64(val).unwrap();
+            assert_eq!(data.len(), var_i64_length(val));
+            assert_eq!(Cursor::new(&data).read_var_i64().unwrap(), val);
+            data.clear();
+        }
+    }
+
     #[test]
     fn var_write() {
         let mut data = Vec::new();
@@ -9,17 +9,29 @@
 //!
 //! Use this module via `#[serde(with = "serde_minecraft::array")]`.
 
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::iter::FromIterator;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
+/// Deserializes an array, failing if the length on the wire differs from the
+/// length of the array.
 pub fn deserialize<'de, I, T, D>(deserializer: D) -> Result<T, D::Error>
 where
     I: Deserialize<'de>,
-    T: FromIterator<I>,
+    T: Default + AsMut<[I]>,
     D: Deserializer<'de>,
 {
-    let values = Vec::deserialize(deserializer)?;
-    Ok(T::from_iter(values))
+    let values = Vec::<I>::deserialize(deserializer)?;
+    let mut arr = T::default();
+
+    let slots = arr.as_mut();
+    if slots.len() != values.len() {
+        let expected = format!("an array of length {}", slots.len());
+        return Err(de::Error::invalid_length(values.len(), &expected.as_str()));
+    }
+    for (slot, value) in slots.iter_mut().zip(values) {
+        *slot = value;
+    }
+
+    Ok(arr)
 }
 
 pub fn serialize<T, S>(arr: T, serializer: S) -> Result<S::Ok, S::Error>
@@ -0,0 +1,195 @@
+//! Strings with a maximum length.
+//!
+//! Many strings in the protocol are much shorter than the general limit of
+//! 32767 UTF-16 units, e.g. usernames may be at most 16 characters long.
+//! `BoundedString` enforces such a limit during deserialization, before the
+//! string is allocated.
+//!
+//! Since the limit is part of the type, it is given as a type implementing
+//! `MaxLength`, which can be declared through `max_length!`:
+//!
+//! ```
+//! use serde_minecraft::{bounded::BoundedString, max_length};
+//!
+//! max_length! {
+//!     /// The maximum length of a username.
+//!     pub UsernameLength = 16;
+//! }
+//!
+//! let name = BoundedString::<UsernameLength>::new("Notch".to_owned());
+//! assert!(name.is_ok());
+//! ```
+
+use serde::{
+    de::{Error, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    marker::PhantomData,
+    ops::Deref,
+};
+
+/// The maximum length of a `BoundedString`, in UTF-16 units.
+pub trait MaxLength {
+    const MAX_LENGTH: usize;
+}
+
+/// Declares types implementing `MaxLength`.
+#[macro_export]
+macro_rules! max_length {
+    ($($(#[$attr:meta])* $vis:vis $name:ident = $len:expr;)*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+            $vis struct $name;
+
+            impl $crate::bounded::MaxLength for $name {
+                const MAX_LENGTH: usize = $len;
+            }
+        )*
+    };
+}
+
+/// A string of at most `L::MAX_LENGTH` UTF-16 units.
+#[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct BoundedString<L> {
+    string: String,
+    _max_length: PhantomData<L>,
+}
+
+impl<L: MaxLength> BoundedString<L> {
+    /// Wraps the given string, or returns it if it is too long.
+    pub fn new(string: String) -> Result<Self, String> {
+        if utf16_len(&string) > L::MAX_LENGTH {
+            return Err(string);
+        }
+
+        Ok(BoundedString {
+            string,
+            _max_length: PhantomData,
+        })
+    }
+}
+
+impl<L> BoundedString<L> {
+    pub fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    pub fn into_inner(self) -> String {
+        self.string
+    }
+}
+
+impl<L> AsRef<str> for BoundedString<L> {
+    fn as_ref(&self) -> &str {
+        &self.string
+    }
+}
+
+impl<L> Debug for BoundedString<L> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        Debug::fmt(&self.string, fmt)
+    }
+}
+
+impl<L> Deref for BoundedString<L> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.string
+    }
+}
+
+impl<L> Display for BoundedString<L> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.string, fmt)
+    }
+}
+
+impl<L> From<BoundedString<L>> for String {
+    fn from(string: BoundedString<L>) -> Self {
+        string.string
+    }
+}
+
+impl<'de, L: MaxLength> Deserialize<'de> for BoundedString<L> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_string(BoundedStringVisitor(PhantomData))
+    }
+}
+
+impl<L> Serialize for BoundedString<L> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.string)
+    }
+}
+
+struct BoundedStringVisitor<L>(PhantomData<L>);
+
+impl<'de, L: MaxLength> Visitor<'de> for BoundedStringVisitor<L> {
+    type Value = BoundedString<L>;
+
+    fn expecting(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "a string of at most {} UTF-16 units", L::MAX_LENGTH)
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        let len = utf16_len(v);
+        if len > L::MAX_LENGTH {
+            return Err(E::invalid_length(len, &self));
+        }
+
+        Ok(BoundedString {
+            string: v.to_owned(),
+            _max_length: PhantomData,
+        })
+    }
+
+    fn visit_string<E: Error>(self, v: String) -> Result<Self::Value, E> {
+        BoundedString::new(v).map_err(|v| E::invalid_length(utf16_len(&v), &self))
+    }
+}
+
+/// The length of the given string in UTF-16 units.
+pub(crate) fn utf16_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_from_slice;
+
+    max_length! {
+        Short = 3;
+    }
+
+    #[test]
+    fn bounded_string() {
+        let s = read_from_slice::<BoundedString<Short>>(&[3, b'a', b'b', b'c']);
+        assert_eq!(s.unwrap().as_str(), "abc");
+
+        // Four UTF-8 bytes, but only two UTF-16 units.
+        let s =
+            read_from_slice::<BoundedString<Short>>(&[4, 0xf0, 0x9f, 0x98, 0x80]);
+        assert_eq!(s.unwrap().as_str(), "\u{1f600}");
+
+        let err =
+            read_from_slice::<BoundedString<Short>>(&[4, b'a', b'b', b'c', b'd'])
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid length 4, expected a string of at most 3 UTF-16 units",
+        );
+
+        assert!(BoundedString::<Short>::new("abcd".to_owned()).is_err());
+    }
+}
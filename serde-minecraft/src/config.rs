@@ -0,0 +1,105 @@
+//! Settings of the (de)serializers.
+
+use crate::{
+    de::Deserializer, error::Error, limits::Limits, position::Layout, read,
+    ser::Serializer,
+};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use serde::{de::Deserialize, ser::Serialize};
+
+/// Settings of the (de)serializers that differ between protocol versions or
+/// applications.
+///
+/// The default config uses the default `Limits` and the block position layout
+/// of the newest versions. The functions of this crate use it, others can be
+/// used through the methods of `Config`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Config {
+    pub(crate) limits: Limits,
+    pub(crate) layout: Layout,
+}
+
+impl Config {
+    /// Sets the limits checked during deserialization.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the layout of block positions, see `position`.
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Like `crate::read_from`, but with this config.
+    pub fn read_from<'de, T: Deserialize<'de>, B: Buf>(
+        self,
+        buf: &'de mut B,
+    ) -> Result<T, Error> {
+        let mut deserializer = Deserializer::new(read::BufRead::new(buf), self);
+        T::deserialize(&mut deserializer)
+    }
+
+    /// Like `crate::read_from_slice`, but with this config.
+    pub fn read_from_slice<'de, T: Deserialize<'de>>(
+        self,
+        slice: &'de [u8],
+    ) -> Result<T, Error> {
+        let mut deserializer = Deserializer::new(read::SliceRead::new(slice), self);
+        T::deserialize(&mut deserializer)
+    }
+
+    /// Like `crate::read_from_bytes`, but with this config.
+    pub fn read_from_bytes<'de, T: Deserialize<'de>>(
+        self,
+        bytes: &'de Bytes,
+    ) -> Result<T, Error> {
+        let mut deserializer = Deserializer::from_bytes(bytes, self);
+        T::deserialize(&mut deserializer)
+    }
+
+    /// Like `crate::read_exact_from`, but with this config.
+    pub fn read_exact_from<'de, T: Deserialize<'de>, B: Buf>(
+        self,
+        buf: &'de mut B,
+    ) -> Result<T, Error> {
+        let mut deserializer = Deserializer::new(read::BufRead::new(buf), self);
+        let val = T::deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(val)
+    }
+
+    /// Like `crate::read_exact_from_bytes`, but with this config.
+    pub fn read_exact_from_bytes<'de, T: Deserialize<'de>>(
+        self,
+        bytes: &'de Bytes,
+    ) -> Result<T, Error> {
+        let mut deserializer = Deserializer::from_bytes(bytes, self);
+        let val = T::deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(val)
+    }
+
+    /// Like `crate::write_to`, but with this config.
+    pub fn write_to<T: Serialize>(
+        self,
+        val: &T,
+        buf: &mut BytesMut,
+    ) -> Result<(), Error> {
+        let size = crate::serialized_size(val)?;
+        buf.reserve(size);
+        self.write_to_no_resize(val, buf)
+    }
+
+    /// Like `crate::write_to_no_resize`, but with this config.
+    pub fn write_to_no_resize<T: Serialize, B: BufMut>(
+        self,
+        val: &T,
+        buf: &mut B,
+    ) -> Result<(), Error> {
+        let mut serializer = Serializer::new(buf, self);
+        val.serialize(&mut serializer)?;
+        Ok(())
+    }
+}
@@ -1,41 +1,80 @@
-use crate::error::Error;
-use bytes::Buf;
+use crate::{
+    bounded::utf16_len,
+    config::Config,
+    error::Error,
+    limits::Limits,
+    nbt,
+    position::{self, Layout},
+    prefixed::Prefix,
+    read::{Read, Reference, SliceRead},
+    rest, shared_bytes,
+};
+use bytes::Bytes;
 use minecraft_varint::VarReadExt;
 use serde::de::{self, *};
-use std::io::ErrorKind;
-
-pub struct Deserializer<'de, B>(&'de mut B);
+use std::{
+    io::{self, ErrorKind},
+    marker::PhantomData,
+    str,
+};
+
+pub struct Deserializer<'de, R> {
+    read: R,
+    limits: Limits,
+    layout: Layout,
+    /// The `Bytes` that `read` reads from, if any, which byte arrays
+    /// deserialized into `Bytes` are sliced out of.
+    input: Option<&'de Bytes>,
+    /// The sum of the lengths of all strings, byte arrays and sequences read
+    /// so far, checked against `Limits::max_alloc`.
+    allocated: usize,
+    _lifetime: PhantomData<&'de ()>,
+}
 
-struct Seq<'a, 'de, B> {
-    de: &'a mut Deserializer<'de, B>,
+struct Seq<'a, 'de, R> {
+    de: &'a mut Deserializer<'de, R>,
     len: usize,
 }
 
-impl<'de, B> Deserializer<'de, B> {
-    pub fn new(buf: &'de mut B) -> Self {
-        Deserializer(buf)
+impl<'de, R> Deserializer<'de, R> {
+    pub fn new(read: R, config: Config) -> Self {
+        Deserializer {
+            read,
+            limits: config.limits,
+            layout: config.layout,
+            input: None,
+            allocated: 0,
+            _lifetime: PhantomData,
+        }
     }
 }
 
-impl<'de, B: Buf> Deserializer<'de, B> {
+impl<'de> Deserializer<'de, SliceRead<'de>> {
+    pub fn from_bytes(bytes: &'de Bytes, config: Config) -> Self {
+        Deserializer {
+            input: Some(bytes),
+            ..Deserializer::new(SliceRead::new(bytes), config)
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> Deserializer<'de, R> {
     fn read_boolean(&mut self) -> Result<bool, Error> {
         self.require_length(1)?;
 
-        match self.0.get_u8() {
+        match self.read.get_u8() {
             0 => Ok(false),
             1 => Ok(true),
-            v => Err(de::Error::invalid_value(
-                Unexpected::Unsigned(v as u64),
-                &"0 or 1",
-            )),
+            v => Err(Error::InvalidBool(v)),
         }
     }
 
     fn read_i32(&mut self) -> Result<i32, Error> {
-        self.0.read_var_i32().map_err(|e| match e.kind() {
-            ErrorKind::UnexpectedEof => Error::UnexpectedEndOfBuffer,
-            e => panic!("unknown error during i32 varint read from memory: {:?}", e),
-        })
+        self.read.read_var_i32().map_err(var_int_error)
+    }
+
+    fn read_i64(&mut self) -> Result<i64, Error> {
+        self.read.read_var_i64().map_err(var_int_error)
     }
 
     fn read_i32_len(&mut self) -> Result<usize, Error> {
@@ -51,18 +90,98 @@ impl<'de, B: Buf> Deserializer<'de, B> {
         Ok(len as usize)
     }
 
-    fn read_bytes(&mut self) -> Result<Vec<u8>, Error> {
-        let len = self.read_i32_len()?;
+    fn read_prefixed_len(&mut self, prefix: Prefix) -> Result<usize, Error> {
+        self.require_length(prefix.size())?;
+
+        let len = match prefix {
+            Prefix::Byte => return Ok(self.read.get_u8() as usize),
+            Prefix::Short => self.read.get_i16_be() as i32,
+            Prefix::Int => self.read.get_i32_be(),
+        };
+
+        if len < 0 {
+            return Err(de::Error::invalid_value(
+                Unexpected::Signed(len as i64),
+                &"positive length",
+            ));
+        }
+
+        Ok(len as usize)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Reference<'de, '_>, Error> {
+        self.reserve(len)?;
         self.require_length(len)?;
 
-        let mut buf = vec![0; len];
-        self.0.copy_to_slice(&mut buf);
+        Ok(self.read.read_slice(len))
+    }
+
+    /// Visits the next `len` bytes as byte array.
+    ///
+    /// Visitors deserializing `Bytes` get a slice of the input instead, if it
+    /// is `Bytes` itself.
+    fn visit_bytes<V: Visitor<'de>>(
+        &mut self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if let Some(input) = self.input {
+            if shared_bytes::is_bytes::<V::Value>() {
+                self.reserve(len)?;
+                self.require_length(len)?;
+
+                let start = input.len() - self.read.remaining();
+                self.read.advance(len);
+                return Ok(shared_bytes::cast(input.slice(start, start + len)));
+            }
+        }
+
+        match self.read_bytes(len)? {
+            Reference::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Reference::Copied(bytes) => visitor.visit_bytes(bytes),
+        }
+    }
+
+    /// Accounts for a string, byte array or sequence of the given length.
+    fn reserve(&mut self, len: usize) -> Result<(), Error> {
+        self.allocated = self.allocated.saturating_add(len);
+
+        if self.allocated > self.limits.max_alloc {
+            Err(Error::AllocationLimitExceeded {
+                max: self.limits.max_alloc,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Visits a sequence of `len` elements, after checking it against the
+    /// limits.
+    fn visit_seq<V: Visitor<'de>>(
+        &mut self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if len > self.limits.max_seq_len {
+            return Err(Error::SequenceTooLong {
+                max: self.limits.max_seq_len,
+            });
+        }
+
+        self.reserve(len)?;
+        visitor.visit_seq(Seq { de: self, len })
+    }
 
-        Ok(buf)
+    /// Ensures the whole input has been consumed.
+    pub fn end(&self) -> Result<(), Error> {
+        match self.read.remaining() {
+            0 => Ok(()),
+            n => Err(Error::TrailingBytes(n)),
+        }
     }
 
     fn require_length(&self, len: usize) -> Result<(), Error> {
-        if self.0.remaining() >= len {
+        if self.read.remaining() >= len {
             Ok(())
         } else {
             Err(Error::UnexpectedEndOfBuffer)
@@ -70,7 +189,7 @@ impl<'de, B: Buf> Deserializer<'de, B> {
     }
 }
 
-impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
+impl<'a, 'de, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<'de, R> {
     type Error = Error;
 
     fn deserialize_any<V>(self, _: V) -> Result<V::Value, Self::Error>
@@ -92,15 +211,15 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         self.require_length(1)?;
-        visitor.visit_i8(self.0.get_i8())
+        visitor.visit_i8(self.read.get_i8())
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.require_length(1)?;
-        visitor.visit_i16(self.0.get_i16_be())
+        self.require_length(2)?;
+        visitor.visit_i16(self.read.get_i16_be())
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -114,12 +233,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
     where
         V: Visitor<'de>,
     {
-        let val = self.0.read_var_i64().map_err(|e| match e.kind() {
-            ErrorKind::UnexpectedEof => Error::UnexpectedEndOfBuffer,
-            _ => unreachable!(),
-        })?;
-
-        visitor.visit_i64(val)
+        visitor.visit_i64(self.read_i64()?)
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -127,7 +241,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         self.require_length(16)?;
-        visitor.visit_i128(self.0.get_i128_be())
+        visitor.visit_i128(self.read.get_i128_be())
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -135,7 +249,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         self.require_length(1)?;
-        visitor.visit_u8(self.0.get_u8())
+        visitor.visit_u8(self.read.get_u8())
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -143,7 +257,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         self.require_length(2)?;
-        visitor.visit_u16(self.0.get_u16_be())
+        visitor.visit_u16(self.read.get_u16_be())
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -151,7 +265,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         self.require_length(4)?;
-        visitor.visit_u32(self.0.get_u32_be())
+        visitor.visit_u32(self.read.get_u32_be())
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -159,7 +273,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         self.require_length(8)?;
-        visitor.visit_u64(self.0.get_u64_be())
+        visitor.visit_u64(self.read.get_u64_be())
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -167,7 +281,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         self.require_length(16)?;
-        visitor.visit_u128(self.0.get_u128_be())
+        visitor.visit_u128(self.read.get_u128_be())
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -175,7 +289,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         self.require_length(4)?;
-        visitor.visit_f32(self.0.get_f32_be())
+        visitor.visit_f32(self.read.get_f32_be())
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -183,7 +297,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         self.require_length(8)?;
-        visitor.visit_f64(self.0.get_f64_be())
+        visitor.visit_f64(self.read.get_f64_be())
     }
 
     fn deserialize_char<V>(self, _: V) -> Result<V::Value, Self::Error>
@@ -204,15 +318,30 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
     where
         V: Visitor<'de>,
     {
-        let bytes = self.read_bytes()?;
-        let string = String::from_utf8(bytes).map_err(|e| {
-            de::Error::invalid_value(
-                Unexpected::Bytes(e.as_bytes()),
-                &"valid UTF-8 bytes",
-            )
-        })?;
+        fn to_str(bytes: &[u8], max: usize) -> Result<&str, Error> {
+            let string = str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?;
+
+            // A string never has more UTF-16 units than UTF-8 bytes.
+            if bytes.len() > max && utf16_len(string) > max {
+                return Err(Error::StringTooLong { max });
+            }
 
-        visitor.visit_string(string)
+            Ok(string)
+        }
+
+        let max = self.limits.max_string_len;
+        let len = self.read_i32_len()?;
+        // Every UTF-16 unit is encoded by at most three UTF-8 bytes.
+        if len > max.saturating_mul(3) {
+            return Err(Error::StringTooLong { max });
+        }
+
+        match self.read_bytes(len)? {
+            Reference::Borrowed(bytes) => {
+                visitor.visit_borrowed_str(to_str(bytes, max)?)
+            }
+            Reference::Copied(bytes) => visitor.visit_str(to_str(bytes, max)?),
+        }
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -226,14 +355,19 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_byte_buf(self.read_bytes()?)
+        let len = self.read_i32_len()?;
+        self.visit_bytes(len, visitor)
     }
 
-    fn deserialize_option<V>(self, _: V) -> Result<V::Value, Self::Error>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Error::Unsupported)
+        if self.read_boolean()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -256,12 +390,35 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
 
     fn deserialize_newtype_struct<V>(
         self,
-        _: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == rest::TOKEN {
+            let len = self.read.remaining();
+            return self.visit_bytes(len, visitor);
+        }
+
+        // Visitors of positions get them in the default layout.
+        if name == position::TOKEN {
+            self.require_length(8)?;
+            let (x, y, z) = self.layout.unpack(self.read.get_u64_be());
+            return visitor.visit_u64(Layout::default().pack(x, y, z));
+        }
+
+        if name == nbt::TOKEN {
+            let bytes = nbt::read_compound(&mut self.read)?;
+            self.reserve(bytes.len())?;
+            return visitor.visit_byte_buf(bytes);
+        }
+
+        if let Some(prefix) = Prefix::from_token(name) {
+            let len = self.read_prefixed_len(prefix)?;
+            return self.visit_seq(len, visitor);
+        }
+
         visitor.visit_newtype_struct(self)
     }
 
@@ -270,7 +427,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
         V: Visitor<'de>,
     {
         let len = self.read_i32_len()?;
-        self.deserialize_tuple(len, visitor)
+        self.visit_seq(len, visitor)
     }
 
     fn deserialize_tuple<V>(
@@ -346,7 +503,7 @@ impl<'a, 'de, B: Buf> de::Deserializer<'de> for &'a mut Deserializer<'de, B> {
     }
 }
 
-impl<'a, 'de, B: Buf> EnumAccess<'de> for &'a mut Deserializer<'de, B> {
+impl<'a, 'de, R: Read<'de>> EnumAccess<'de> for &'a mut Deserializer<'de, R> {
     type Error = Error;
     type Variant = Self;
 
@@ -371,40 +528,44 @@ impl<'a, 'de, B: Buf> EnumAccess<'de> for &'a mut Deserializer<'de, B> {
     }
 }
 
-impl<'a, 'de, B: Buf> VariantAccess<'de> for &'a mut Deserializer<'de, B> {
+impl<'a, 'de, R: Read<'de>> VariantAccess<'de> for &'a mut Deserializer<'de, R> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
         Ok(())
     }
 
-    fn newtype_variant_seed<T>(self, _: T) -> Result<T::Value, Self::Error>
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
     where
         T: DeserializeSeed<'de>,
     {
-        Err(Error::Unsupported)
+        seed.deserialize(self)
     }
 
-    fn tuple_variant<V>(self, _: usize, _: V) -> Result<V::Value, Self::Error>
+    fn tuple_variant<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Error::Unsupported)
+        de::Deserializer::deserialize_tuple(self, len, visitor)
     }
 
     fn struct_variant<V>(
         self,
-        _: &'static [&'static str],
-        _: V,
+        fields: &'static [&'static str],
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Error::Unsupported)
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
     }
 }
 
-impl<'a, 'de, B: Buf> SeqAccess<'de> for Seq<'a, 'de, B> {
+impl<'a, 'de, R: Read<'de>> SeqAccess<'de> for Seq<'a, 'de, R> {
     type Error = Error;
 
     fn next_element_seed<T>(
@@ -427,3 +588,11 @@ impl<'a, 'de, B: Buf> SeqAccess<'de> for Seq<'a, 'de, B> {
         Some(self.len)
     }
 }
+
+/// Converts an error from reading a VarInt or VarLong from memory.
+fn var_int_error(e: io::Error) -> Error {
+    match e.kind() {
+        ErrorKind::UnexpectedEof => Error::UnexpectedEndOfBuffer,
+        _ => Error::VarIntTooLong,
+    }
+}
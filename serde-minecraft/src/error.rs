@@ -6,12 +6,45 @@ use std::{
 
 #[derive(Debug)]
 pub enum Error {
+    /// A value could not be serialized, raised through
+    /// `serde::ser::Error::custom`.
     Custom(String),
 
+    /// The input did not describe a valid value, e.g. an unknown enum variant,
+    /// raised through `serde::de::Error::custom`.
+    InvalidValue(String),
+
     LengthRequired,
 
     UnexpectedEndOfBuffer,
 
+    /// A VarInt or VarLong was longer than its maximum length.
+    VarIntTooLong,
+
+    /// A string was not valid UTF-8.
+    InvalidUtf8,
+
+    /// A boolean was neither 0 nor 1.
+    InvalidBool(u8),
+
+    /// Input remained after deserializing a value.
+    TrailingBytes(usize),
+
+    /// A string was longer than `Limits::max_string_len`.
+    StringTooLong {
+        max: usize,
+    },
+
+    /// A sequence was longer than `Limits::max_seq_len`.
+    SequenceTooLong {
+        max: usize,
+    },
+
+    /// A value was larger than `Limits::max_alloc`.
+    AllocationLimitExceeded {
+        max: usize,
+    },
+
     Unsupported,
 
     #[doc(hidden)]
@@ -25,7 +58,7 @@ impl serde::de::Error for Error {
     where
         T: Display,
     {
-        Error::Custom(msg.to_string())
+        Error::InvalidValue(msg.to_string())
     }
 }
 
@@ -41,25 +74,57 @@ impl serde::ser::Error for Error {
 impl Display for Error {
     fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
         match self {
-            Error::Custom(msg) => fmt.write_str(msg),
+            Error::Custom(msg) | Error::InvalidValue(msg) => fmt.write_str(msg),
             Error::LengthRequired => {
                 write!(fmt, "serializing sequences requires specifying a length")
             }
             Error::UnexpectedEndOfBuffer => write!(fmt, "unexpected end of buffer"),
+            Error::VarIntTooLong => write!(fmt, "VarInt too long"),
+            Error::InvalidUtf8 => write!(fmt, "invalid UTF-8 in string"),
+            Error::InvalidBool(v) => write!(fmt, "invalid boolean value {}", v),
+            Error::TrailingBytes(n) => {
+                write!(fmt, "{} bytes remaining after deserializing", n)
+            }
+            Error::StringTooLong { max } => {
+                write!(fmt, "string longer than {} UTF-16 units", max)
+            }
+            Error::SequenceTooLong { max } => {
+                write!(fmt, "sequence longer than {} elements", max)
+            }
+            Error::AllocationLimitExceeded { max } => write!(
+                fmt,
+                "strings, byte arrays and sequences longer than {} in total",
+                max,
+            ),
             Error::Unsupported => write!(fmt, "unsupported data type"),
             Error::__NonExhaustive => write!(fmt, "unknown"),
         }
     }
 }
 
+/// Converts the error, with all errors caused by invalid input being
+/// `ErrorKind::InvalidData`.
+///
+/// Packets are only deserialized once they have been received completely, so
+/// running out of input means the packet is malformed.
 impl From<Error> for io::Error {
     fn from(e: Error) -> Self {
         match e {
             Error::Custom(v) => io::Error::new(ErrorKind::Other, v),
+            Error::InvalidValue(v) => io::Error::new(ErrorKind::InvalidData, v),
             Error::LengthRequired => {
                 io::Error::new(ErrorKind::InvalidData, "length required")
             }
-            Error::UnexpectedEndOfBuffer => ErrorKind::UnexpectedEof.into(),
+            e @ Error::UnexpectedEndOfBuffer
+            | e @ Error::VarIntTooLong
+            | e @ Error::InvalidUtf8
+            | e @ Error::InvalidBool(_)
+            | e @ Error::TrailingBytes(_)
+            | e @ Error::StringTooLong { .. }
+            | e @ Error::SequenceTooLong { .. }
+            | e @ Error::AllocationLimitExceeded { .. } => {
+                io::Error::new(ErrorKind::InvalidData, e.to_string())
+            }
             Error::Unsupported => io::Error::new(ErrorKind::Other, "unsupported"),
             _ => ErrorKind::Other.into(),
         }
@@ -0,0 +1,77 @@
+//! (De)serialize values as JSON strings.
+//!
+//! Several fields of the protocol, e.g. chat messages and disconnect reasons,
+//! are strings containing JSON. This module converts the value to and from
+//! JSON and writes it as such a string.
+//!
+//! Human-readable formats see the value itself instead of a string holding
+//! its JSON, so it is not encoded twice when e.g. logging packets as JSON.
+//!
+//! Use this module via `#[serde(with = "serde_minecraft::json")]`.
+
+use serde::{
+    de::{self, Deserialize, DeserializeOwned, Deserializer},
+    ser::{self, Serialize, Serializer},
+};
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: DeserializeOwned,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        return T::deserialize(deserializer);
+    }
+
+    let json = String::deserialize(deserializer)?;
+    serde_json::from_str(&json).map_err(de::Error::custom)
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        return value.serialize(serializer);
+    }
+
+    let json = serde_json::to_string(value).map_err(ser::Error::custom)?;
+    serializer.serialize_str(&json)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{read_from_slice, write_to};
+    use bytes::BytesMut;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Message {
+        text: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Packet {
+        #[serde(with = "super")]
+        message: Message,
+        position: u8,
+    }
+
+    #[test]
+    fn string_field() {
+        let packet = Packet {
+            message: Message {
+                text: "hi".to_owned(),
+            },
+            position: 1,
+        };
+
+        let mut buf = BytesMut::new();
+        write_to(&packet, &mut buf).unwrap();
+        assert_eq!(&buf[..], &b"\x0d{\"text\":\"hi\"}\x01"[..]);
+        assert_eq!(read_from_slice::<Packet>(&buf).unwrap(), packet);
+
+        assert!(read_from_slice::<Packet>(b"\x02{}\x01").is_err());
+    }
+}
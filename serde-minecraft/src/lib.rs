@@ -1,25 +1,56 @@
 //! Serde support for the minecraft network format.
 //!
-//! This crate supports structs, tuples, tuple structs, units, and unit enums.
-//! Enums with data (this includes `Option<T>` and `Result<T, E>`) are _not_
-//! supported, because the network format does not model that.
+//! This crate supports structs, tuples, tuple structs, units, enums and
+//! `Option<T>`. Maps are _not_ supported, because the network format does not
+//! model them.
+//!
+//! Enums are encoded as tagged unions: the index of the variant (in
+//! declaration order, starting at zero) as VarInt, followed by the data of
+//! the variant, if any. Options are encoded like the protocol's optional
+//! fields: a boolean telling whether the value is present, followed by the
+//! value if it is.
+//!
+//! Sequences are prefixed with their length as VarInt, unless they use one of
+//! the `prefixed` modules. Fixed-size arrays are encoded like tuples, without
+//! any length prefix; use `array` to prefix them. Strings holding JSON, like
+//! chat messages, can be (de)serialized through `json`.
 //!
 //! Minecraft's network format is not self-describing. Therefore, when using
 //! serde's derive feature, ensure your struct members are listed in the same
 //! order as specified in the format specification. Otherwise the library
 //! will yield garbage values.
+//!
+//! `read_from` copies strings and byte arrays out of the buffer. To avoid
+//! that, `read_from_slice` and `read_from_bytes` can hand out `&str` and
+//! `&[u8]` borrowed from the input, and `Bytes` sharing its memory (see
+//! `shared_bytes`).
+//!
+//! Lengths read from the wire are checked against `Limits` before anything is
+//! allocated for them. The functions of this module use the default limits,
+//! others can be used through the methods of `Limits`, or those of `Config`,
+//! which also holds the layout of block positions (see `position`).
 
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use serde::{de::Deserialize, ser::Serialize};
 
 pub mod array;
+pub mod bounded;
 pub mod fixed_i32;
 pub mod fixed_i64;
+pub mod json;
+pub mod nbt;
+pub mod position;
+pub mod prefixed;
+pub mod rest;
+pub mod shared_bytes;
 
-pub use error::*;
+pub use self::{config::Config, error::*, limits::Limits};
 
+mod config;
 mod de;
 mod error;
+mod limits;
+mod read;
 mod ser;
 mod size;
 
@@ -27,8 +58,41 @@ mod size;
 pub fn read_from<'de, T: Deserialize<'de>, B: Buf>(
     buf: &'de mut B,
 ) -> Result<T, Error> {
-    let mut deserializer = de::Deserializer::new(buf);
-    T::deserialize(&mut deserializer)
+    Config::default().read_from(buf)
+}
+
+/// Attempts to deserialize a `T` in minecraft network format from the given
+/// slice, borrowing strings and byte arrays from it.
+pub fn read_from_slice<'de, T: Deserialize<'de>>(
+    slice: &'de [u8],
+) -> Result<T, Error> {
+    Config::default().read_from_slice(slice)
+}
+
+/// Attempts to deserialize a `T` in minecraft network format from the given
+/// `Bytes`, borrowing strings and byte arrays from it.
+///
+/// Fields using `shared_bytes` are sliced out of `bytes` without copying.
+pub fn read_from_bytes<'de, T: Deserialize<'de>>(
+    bytes: &'de Bytes,
+) -> Result<T, Error> {
+    Config::default().read_from_bytes(bytes)
+}
+
+/// Like `read_from`, but fails with `Error::TrailingBytes` unless the whole
+/// buffer was consumed.
+pub fn read_exact_from<'de, T: Deserialize<'de>, B: Buf>(
+    buf: &'de mut B,
+) -> Result<T, Error> {
+    Config::default().read_exact_from(buf)
+}
+
+/// Like `read_from_bytes`, but fails with `Error::TrailingBytes` unless all of
+/// `bytes` was consumed.
+pub fn read_exact_from_bytes<'de, T: Deserialize<'de>>(
+    bytes: &'de Bytes,
+) -> Result<T, Error> {
+    Config::default().read_exact_from_bytes(bytes)
 }
 
 /// Computes the size of the given `T` if it was written to minecraft network
@@ -45,9 +109,7 @@ pub fn serialized_size<T: Serialize>(val: &T) -> Result<usize, Error> {
 
 /// Writes the given `T` into the given `BytesMut`, reserving space as necessary.
 pub fn write_to<T: Serialize>(val: &T, buf: &mut BytesMut) -> Result<(), Error> {
-    let size = serialized_size(val)?;
-    buf.reserve(size);
-    write_to_no_resize(val, buf)
+    Config::default().write_to(val, buf)
 }
 
 /// Writes the given `T` into the given `BufMut`, without reserving space
@@ -58,7 +120,304 @@ pub fn write_to_no_resize<T: Serialize, B: BufMut>(
     val: &T,
     buf: &mut B,
 ) -> Result<(), Error> {
-    let mut serializer = ser::Serializer::new(buf);
-    val.serialize(&mut serializer)?;
-    Ok(())
+    Config::default().write_to_no_resize(val, buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::IntoBuf;
+    use serde::{Deserialize, Serialize};
+    use std::{fmt::Debug, io};
+
+    fn round_trip<T>(val: &T, expected: &[u8])
+    where
+        T: Debug + PartialEq + Serialize + for<'de> Deserialize<'de>,
+    {
+        let mut buf = BytesMut::new();
+        write_to(val, &mut buf).unwrap();
+        assert_eq!(&buf[..], expected);
+        assert_eq!(serialized_size(val).unwrap(), expected.len());
+
+        let mut read_buf = buf.freeze().into_buf();
+        assert_eq!(&read_from::<T, _>(&mut read_buf).unwrap(), val);
+        assert_eq!(read_buf.remaining(), 0);
+    }
+
+    #[test]
+    fn option() {
+        round_trip(&None::<i32>, &[0]);
+        round_trip(&Some(300i32), &[1, 0xac, 0x02]);
+        round_trip(&Some("hi".to_owned()), &[1, 2, b'h', b'i']);
+        round_trip(&(Some(1u8), None::<u16>, 2u8), &[1, 1, 0, 2]);
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    enum Action {
+        Remove,
+        Rename(String),
+        Move(i8, i8),
+        Resize { width: u8, height: u8 },
+    }
+
+    #[test]
+    fn enum_variants() {
+        round_trip(&Action::Remove, &[0]);
+        round_trip(&Action::Rename("a".to_owned()), &[1, 1, b'a']);
+        round_trip(&Action::Move(-1, 2), &[2, 0xff, 2]);
+        round_trip(
+            &Action::Resize {
+                width: 3,
+                height: 4,
+            },
+            &[3, 3, 4],
+        );
+        round_trip(&vec![Action::Remove, Action::Move(0, 0)], &[2, 0, 2, 0, 0]);
+    }
+
+    #[test]
+    fn enum_invalid_variant() {
+        let mut buf = (&[4u8][..]).into_buf();
+        assert!(read_from::<Action, _>(&mut buf).is_err());
+    }
+
+    #[test]
+    fn option_invalid_prefix() {
+        let mut buf = (&[2u8, 0][..]).into_buf();
+        assert!(read_from::<Option<u8>, _>(&mut buf).is_err());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        #[serde(with = "serde_bytes_ref")]
+        data: &'a [u8],
+    }
+
+    mod serde_bytes_ref {
+        use serde::{Deserialize, Deserializer};
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<&'de [u8], D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            <&[u8]>::deserialize(deserializer)
+        }
+    }
+
+    #[test]
+    fn borrowed() {
+        let input = [2, b'h', b'i', 3, 1, 2, 3];
+        let val = read_from_slice::<Borrowed>(&input).unwrap();
+
+        assert_eq!(val.name, "hi");
+        assert_eq!(val.name.as_ptr(), input[1..].as_ptr());
+        assert_eq!(val.data, &[1, 2, 3]);
+        assert_eq!(val.data.as_ptr(), input[4..].as_ptr());
+
+        // Buffers cannot lend out their contents.
+        let mut buf = (&input[..]).into_buf();
+        assert!(read_from::<Borrowed, _>(&mut buf).is_err());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Shared {
+        id: u8,
+        #[serde(with = "shared_bytes")]
+        data: Bytes,
+    }
+
+    #[test]
+    fn shared() {
+        let val = Shared {
+            id: 1,
+            data: Bytes::from(vec![0xab; 64]),
+        };
+        let mut expected = vec![1, 64];
+        expected.extend_from_slice(&[0xab; 64]);
+        round_trip(&val, &expected);
+
+        let input = Bytes::from(expected);
+        let read = read_from_bytes::<Shared>(&input).unwrap();
+        assert_eq!(read, val);
+        assert_eq!(read.data.as_ptr(), input[2..].as_ptr());
+    }
+
+    #[test]
+    fn limits() {
+        let limits = Limits::default()
+            .max_string_len(2)
+            .max_seq_len(3)
+            .max_alloc(5);
+
+        assert!(limits.read_from_slice::<String>(&[2, b'a', b'b']).is_ok());
+        match limits.read_from_slice::<String>(&[3, b'a', b'b', b'c']) {
+            Err(Error::StringTooLong { max: 2 }) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        // Rejected before reading, the bytes need not be present.
+        match limits.read_from_slice::<String>(&[7]) {
+            Err(Error::StringTooLong { max: 2 }) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        match limits.read_from_slice::<Vec<u8>>(&[0xff, 0xff, 0xff, 0xff, 0x07]) {
+            Err(Error::SequenceTooLong { max: 3 }) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        match limits.read_from_slice::<Vec<()>>(&[4]) {
+            Err(Error::SequenceTooLong { max: 3 }) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        let input = [3, 1, b'a', 1, b'b', 1, b'c'];
+        match limits.read_from_slice::<Vec<String>>(&input) {
+            Err(Error::AllocationLimitExceeded { max: 5 }) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct WithRest {
+        channel: String,
+        #[serde(with = "rest")]
+        data: Bytes,
+    }
+
+    #[test]
+    fn rest() {
+        let val = WithRest {
+            channel: "a".to_owned(),
+            data: Bytes::from(&[1, 2, 3][..]),
+        };
+        round_trip(&val, &[1, b'a', 1, 2, 3]);
+
+        let empty = WithRest {
+            channel: "a".to_owned(),
+            data: Bytes::new(),
+        };
+        round_trip(&empty, &[1, b'a']);
+
+        let input = Bytes::from(vec![1, b'a', 0xff, 0xfe]);
+        let read = read_exact_from_bytes::<WithRest>(&input).unwrap();
+        assert_eq!(read.data, &[0xff, 0xfe][..]);
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Prefixed {
+        #[serde(with = "prefixed::byte")]
+        bytes: Vec<u8>,
+        #[serde(with = "prefixed::short")]
+        shorts: Vec<i16>,
+        #[serde(with = "prefixed::int")]
+        strings: Vec<String>,
+        fixed: [u8; 2],
+    }
+
+    #[test]
+    fn prefixed() {
+        let val = Prefixed {
+            bytes: vec![7],
+            shorts: vec![1, 2],
+            strings: vec!["a".to_owned()],
+            fixed: [8, 9],
+        };
+        #[rustfmt::skip]
+        round_trip(&val, &[
+            1, 7,
+            0, 2, 0, 1, 0, 2,
+            0, 0, 0, 1, 1, b'a',
+            8, 9,
+        ]);
+
+        let mut buf = BytesMut::new();
+        let mut val = val;
+        val.bytes = vec![0; 256];
+        assert!(write_to(&val, &mut buf).is_err());
+
+        let limits = Limits::default().max_seq_len(1);
+        match limits.read_from_slice::<Prefixed>(&[0, 0, 2]) {
+            Err(Error::SequenceTooLong { max: 1 }) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        assert!(read_from_slice::<Prefixed>(&[0, 0xff, 0xff]).is_err());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Token {
+        #[serde(with = "array")]
+        bytes: [u8; 4],
+    }
+
+    #[test]
+    fn array() {
+        let token = Token {
+            bytes: [1, 2, 3, 4],
+        };
+        round_trip(&token, &[4, 1, 2, 3, 4]);
+
+        assert!(read_from_slice::<Token>(&[3, 1, 2, 3]).is_err());
+        assert!(read_from_slice::<Token>(&[5, 1, 2, 3, 4, 5]).is_err());
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let input = Bytes::from(&[1, 2, 3][..]);
+        assert_eq!(
+            read_exact_from_bytes::<(u8, u16)>(&input).unwrap(),
+            (1, 0x0203)
+        );
+        match read_exact_from_bytes::<u16>(&input) {
+            Err(Error::TrailingBytes(1)) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        assert_eq!(read_from_bytes::<u16>(&input).unwrap(), 0x0102);
+
+        let mut buf = input.into_buf();
+        match read_exact_from::<u8, _>(&mut buf) {
+            Err(Error::TrailingBytes(2)) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn io_error_kinds() {
+        let kind = |e: Error| io::Error::from(e).kind();
+
+        let invalid_variant = read_from_slice::<Action>(&[4]).unwrap_err();
+        assert_eq!(kind(invalid_variant), io::ErrorKind::InvalidData);
+        let truncated = read_from_slice::<i16>(&[0xff]).unwrap_err();
+        assert_eq!(kind(truncated), io::ErrorKind::InvalidData);
+
+        let unserializable = <Error as serde::ser::Error>::custom("unsupported");
+        assert_eq!(kind(unserializable), io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn invalid_input() {
+        match read_from_slice::<String>(&[1, 0xff]) {
+            Err(Error::InvalidUtf8) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        match read_from_slice::<&str>(&[1, 0xff]) {
+            Err(Error::InvalidUtf8) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        match read_from_slice::<bool>(&[2]) {
+            Err(Error::InvalidBool(2)) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        match read_from_slice::<i32>(&[0xff, 0xff, 0xff, 0xff, 0xff, 0x01]) {
+            Err(Error::VarIntTooLong) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        match read_from_slice::<i64>(&[0xff; 11]) {
+            Err(Error::VarIntTooLong) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        match read_from_slice::<i16>(&[0xff]) {
+            Err(Error::UnexpectedEndOfBuffer) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
 }
@@ -0,0 +1,96 @@
+//! Limits protecting the deserializer against malicious input.
+
+use crate::{config::Config, error::Error};
+use bytes::{Buf, Bytes};
+use serde::de::Deserialize;
+
+/// The maximum length of a (decompressed) packet vanilla accepts.
+const MAX_PACKET_LEN: usize = 2_097_152;
+
+/// Limits on the lengths read from the wire during deserialization.
+///
+/// Lengths on the wire are controlled by the peer, so they must not be
+/// trusted blindly. Values exceeding a limit make deserialization fail
+/// before anything is allocated for them.
+///
+/// The default limits are those of vanilla: strings of at most 32767 UTF-16
+/// units and nothing longer than the largest packet vanilla accepts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Limits {
+    pub(crate) max_string_len: usize,
+    pub(crate) max_seq_len: usize,
+    pub(crate) max_alloc: usize,
+}
+
+impl Limits {
+    /// Sets the maximum length of strings, in UTF-16 units like vanilla.
+    pub fn max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = len;
+        self
+    }
+
+    /// Sets the maximum number of elements of sequences.
+    pub fn max_seq_len(mut self, len: usize) -> Self {
+        self.max_seq_len = len;
+        self
+    }
+
+    /// Sets the maximum sum of the lengths of all strings, byte arrays and
+    /// sequences in a value.
+    ///
+    /// This bounds the memory allocated while deserializing a value.
+    pub fn max_alloc(mut self, len: usize) -> Self {
+        self.max_alloc = len;
+        self
+    }
+
+    /// Like `crate::read_from`, but with these limits.
+    pub fn read_from<'de, T: Deserialize<'de>, B: Buf>(
+        self,
+        buf: &'de mut B,
+    ) -> Result<T, Error> {
+        Config::default().limits(self).read_from(buf)
+    }
+
+    /// Like `crate::read_from_slice`, but with these limits.
+    pub fn read_from_slice<'de, T: Deserialize<'de>>(
+        self,
+        slice: &'de [u8],
+    ) -> Result<T, Error> {
+        Config::default().limits(self).read_from_slice(slice)
+    }
+
+    /// Like `crate::read_from_bytes`, but with these limits.
+    pub fn read_from_bytes<'de, T: Deserialize<'de>>(
+        self,
+        bytes: &'de Bytes,
+    ) -> Result<T, Error> {
+        Config::default().limits(self).read_from_bytes(bytes)
+    }
+
+    /// Like `crate::read_exact_from`, but with these limits.
+    pub fn read_exact_from<'de, T: Deserialize<'de>, B: Buf>(
+        self,
+        buf: &'de mut B,
+    ) -> Result<T, Error> {
+        Config::default().limits(self).read_exact_from(buf)
+    }
+
+    /// Like `crate::read_exact_from_bytes`, but with these limits.
+    pub fn read_exact_from_bytes<'de, T: Deserialize<'de>>(
+        self,
+        bytes: &'de Bytes,
+    ) -> Result<T, Error> {
+        Config::default().limits(self).read_exact_from_bytes(bytes)
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_string_len: 32767,
+            max_seq_len: MAX_PACKET_LEN,
+            max_alloc: MAX_PACKET_LEN,
+        }
+    }
+}
@@ -0,0 +1,417 @@
+//! (De)serialize NBT compounds embedded in packets.
+//!
+//! Some packets embed NBT compounds, e.g. the heightmaps of Chunk Data or the
+//! data of block entities. NBT has no length prefix, so such fields are read
+//! by walking the tags until the end of the compound, before the compound is
+//! decoded through `hematite-nbt`.
+//!
+//! Compounds can be held as `nbt::Blob`, which keeps the exact tags, or as
+//! any serde type wrapped in `Nbt`. Where the protocol allows leaving out the
+//! compound (e.g. in slots), a single `TAG_End` is sent instead, which the
+//! `option` submodule maps to `None`.
+//!
+//! Use this module via `#[serde(with = "serde_minecraft::nbt")]`, or its
+//! submodules for optional compounds and sequences of compounds.
+
+use crate::{error::Error, rest::Raw};
+use bytes::Buf;
+use serde::{
+    de::{self, DeserializeOwned, Visitor},
+    ser, Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::fmt;
+
+/// The name of the newtype struct the (de)serializers treat as NBT compound.
+pub(crate) const TOKEN: &str = "$serde_minecraft::nbt";
+
+/// The maximum nesting depth of compounds and lists, like vanilla.
+const MAX_DEPTH: usize = 512;
+
+const TAG_END: u8 = 0;
+const TAG_COMPOUND: u8 = 10;
+
+/// A type that is sent as NBT compound.
+pub trait Compound: Sized {
+    fn to_nbt(&self, dst: &mut Vec<u8>) -> ::nbt::Result<()>;
+
+    fn from_nbt(src: &[u8]) -> ::nbt::Result<Self>;
+}
+
+/// A serde type sent as NBT compound.
+///
+/// Note that `hematite-nbt` sends sequences as lists, never as arrays.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Nbt<T>(pub T);
+
+impl Compound for ::nbt::Blob {
+    fn to_nbt(&self, dst: &mut Vec<u8>) -> ::nbt::Result<()> {
+        self.to_writer(dst)
+    }
+
+    fn from_nbt(mut src: &[u8]) -> ::nbt::Result<Self> {
+        ::nbt::Blob::from_reader(&mut src)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Compound for Nbt<T> {
+    fn to_nbt(&self, dst: &mut Vec<u8>) -> ::nbt::Result<()> {
+        ::nbt::to_writer(dst, &self.0, None)
+    }
+
+    fn from_nbt(src: &[u8]) -> ::nbt::Result<Self> {
+        ::nbt::from_reader(src).map(Nbt)
+    }
+}
+
+impl<'de, T: Serialize + DeserializeOwned> Deserialize<'de> for Nbt<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(deserializer)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Serialize for Nbt<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(self, serializer)
+    }
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Compound,
+    D: Deserializer<'de>,
+{
+    let bytes = deserializer.deserialize_newtype_struct(TOKEN, NbtVisitor)?;
+    T::from_nbt(&bytes).map_err(de::Error::custom)
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Compound,
+    S: Serializer,
+{
+    let mut bytes = Vec::new();
+    value.to_nbt(&mut bytes).map_err(ser::Error::custom)?;
+    serializer.serialize_newtype_struct(TOKEN, &Raw(&bytes))
+}
+
+/// Optional NBT compounds, sent as `TAG_End` if absent.
+pub mod option {
+    use super::*;
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: Compound,
+        D: Deserializer<'de>,
+    {
+        let bytes = deserializer.deserialize_newtype_struct(TOKEN, NbtVisitor)?;
+        if bytes == [TAG_END] {
+            return Ok(None);
+        }
+
+        T::from_nbt(&bytes).map(Some).map_err(de::Error::custom)
+    }
+
+    pub fn serialize<T, S>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Compound,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_newtype_struct(TOKEN, &Raw(&[TAG_END])),
+        }
+    }
+}
+
+/// Sequences of NBT compounds, prefixed with their length as VarInt.
+pub mod seq {
+    use super::*;
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: Compound,
+        D: Deserializer<'de>,
+    {
+        let elements = Vec::<Element<T>>::deserialize(deserializer)?;
+        Ok(elements.into_iter().map(|e| e.0).collect())
+    }
+
+    pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Compound,
+        S: Serializer,
+    {
+        serializer.collect_seq(values.iter().map(ElementRef))
+    }
+
+    struct Element<T>(T);
+
+    impl<'de, T: Compound> Deserialize<'de> for Element<T> {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            super::deserialize(d).map(Element)
+        }
+    }
+
+    struct ElementRef<'a, T>(&'a T);
+
+    impl<'a, T: Compound> Serialize for ElementRef<'a, T> {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            super::serialize(self.0, s)
+        }
+    }
+}
+
+/// Reads the NBT compound (or single `TAG_End`) at the start of `buf`.
+///
+/// The tags are validated just enough to find the end of the compound
+/// without trusting any length, the rest is left to `hematite-nbt`.
+pub(crate) fn read_compound<B: Buf>(buf: &mut B) -> Result<Vec<u8>, Error> {
+    let mut reader = TagReader {
+        buf,
+        bytes: Vec::new(),
+    };
+
+    match reader.u8()? {
+        TAG_END => {}
+        TAG_COMPOUND => {
+            reader.string()?;
+            reader.payload(TAG_COMPOUND, 0)?;
+        }
+        tag => return Err(invalid_tag(tag)),
+    }
+
+    Ok(reader.bytes)
+}
+
+/// Copies the tags it reads into `bytes`.
+struct TagReader<'a, B> {
+    buf: &'a mut B,
+    bytes: Vec<u8>,
+}
+
+impl<'a, B: Buf> TagReader<'a, B> {
+    fn take(&mut self, len: usize) -> Result<&[u8], Error> {
+        if self.buf.remaining() < len {
+            return Err(Error::UnexpectedEndOfBuffer);
+        }
+
+        let start = self.bytes.len();
+        self.bytes.resize(start + len, 0);
+        self.buf.copy_to_slice(&mut self.bytes[start..]);
+        Ok(&self.bytes[start..])
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), Error> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.take(2)?;
+        Ok(u16::from(bytes[0]) << 8 | u16::from(bytes[1]))
+    }
+
+    fn i32(&mut self) -> Result<i32, Error> {
+        let bytes = self.take(4)?;
+        Ok(bytes.iter().fold(0, |val, &b| val << 8 | i32::from(b)))
+    }
+
+    fn len(&mut self) -> Result<usize, Error> {
+        match self.i32()? {
+            len if len < 0 => Err(de::Error::custom("negative NBT length")),
+            len => Ok(len as usize),
+        }
+    }
+
+    fn string(&mut self) -> Result<(), Error> {
+        let len = self.u16()?;
+        self.skip(usize::from(len))
+    }
+
+    fn array(&mut self, elem_size: usize) -> Result<(), Error> {
+        let len = self.len()?;
+        self.skip(len.saturating_mul(elem_size))
+    }
+
+    fn payload(&mut self, tag: u8, depth: usize) -> Result<(), Error> {
+        match tag {
+            1 => self.skip(1),
+            2 => self.skip(2),
+            3 | 5 => self.skip(4),
+            4 | 6 => self.skip(8),
+            7 => self.array(1),
+            8 => self.string(),
+            9 => {
+                let depth = enter(depth)?;
+                let elem_tag = self.u8()?;
+                let len = self.len()?;
+                if elem_tag == TAG_END && len != 0 {
+                    return Err(invalid_tag(elem_tag));
+                }
+
+                for _ in 0..len {
+                    self.payload(elem_tag, depth)?;
+                }
+                Ok(())
+            }
+            TAG_COMPOUND => {
+                let depth = enter(depth)?;
+                loop {
+                    match self.u8()? {
+                        TAG_END => return Ok(()),
+                        tag => {
+                            self.string()?;
+                            self.payload(tag, depth)?;
+                        }
+                    }
+                }
+            }
+            11 => self.array(4),
+            12 => self.array(8),
+            tag => Err(invalid_tag(tag)),
+        }
+    }
+}
+
+fn enter(depth: usize) -> Result<usize, Error> {
+    if depth >= MAX_DEPTH {
+        Err(de::Error::custom("NBT nested too deeply"))
+    } else {
+        Ok(depth + 1)
+    }
+}
+
+fn invalid_tag(tag: u8) -> Error {
+    de::Error::custom(format!("invalid NBT tag {}", tag))
+}
+
+struct NbtVisitor;
+
+impl<'de> Visitor<'de> for NbtVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("an NBT compound")
+    }
+
+    // Other formats see a newtype struct around a regular byte array.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(self)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_owned())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_exact_from, read_from_slice, serialized_size, write_to};
+    use bytes::{BytesMut, IntoBuf};
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Packet {
+        id: i32,
+        #[serde(with = "super")]
+        heightmaps: ::nbt::Blob,
+        #[serde(with = "option")]
+        tag: Option<::nbt::Blob>,
+        #[serde(with = "seq")]
+        block_entities: Vec<::nbt::Blob>,
+    }
+
+    #[test]
+    fn blob() {
+        let mut heightmaps = ::nbt::Blob::new();
+        heightmaps.insert("HEIGHTS", vec![-1i64]).unwrap();
+        let mut block_entity = ::nbt::Blob::new();
+        block_entity.insert("x", 1i32).unwrap();
+
+        let val = Packet {
+            id: 1,
+            heightmaps,
+            tag: None,
+            block_entities: vec![block_entity],
+        };
+
+        let mut buf = BytesMut::new();
+        write_to(&val, &mut buf).unwrap();
+        assert_eq!(serialized_size(&val).unwrap(), buf.len());
+
+        #[rustfmt::skip]
+        assert_eq!(&buf[..], &[
+            1,
+            // TAG_Compound ''
+            10, 0, 0,
+            // TAG_Long_Array 'HEIGHTS'
+            12, 0, 7, b'H', b'E', b'I', b'G', b'H', b'T', b'S',
+            0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0,
+            // TAG_End
+            0,
+            1,
+            // TAG_Compound '' { TAG_Int 'x': 1 }
+            10, 0, 0, 3, 0, 1, b'x', 0, 0, 0, 1, 0,
+        ][..]);
+
+        let mut read_buf = buf.freeze().into_buf();
+        assert_eq!(read_exact_from::<Packet, _>(&mut read_buf).unwrap(), val);
+    }
+
+    #[test]
+    fn serde_type() {
+        let mut map = HashMap::new();
+        map.insert("a".to_owned(), 2);
+        let val = Some(Nbt(map));
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Slot(#[serde(with = "option")] Option<Nbt<HashMap<String, i32>>>);
+
+        let mut buf = BytesMut::new();
+        write_to(&Slot(val.clone()), &mut buf).unwrap();
+        assert_eq!(&buf[..], &[10, 0, 0, 3, 0, 1, b'a', 0, 0, 0, 2, 0]);
+        assert_eq!(read_from_slice::<Slot>(&buf).unwrap(), Slot(val));
+    }
+
+    #[test]
+    fn invalid() {
+        type Blob = ::nbt::Blob;
+
+        // Missing TAG_End
+        assert!(read_from_slice::<Nbt<Blob>>(&[10, 0, 0]).is_err());
+        // TAG_End where a compound is required
+        assert!(read_from_slice::<Nbt<Blob>>(&[0]).is_err());
+        // Invalid tag
+        assert!(read_from_slice::<Nbt<Blob>>(&[10, 0, 0, 13, 0, 0, 0]).is_err());
+        // Byte array longer than the input
+        let input = [10, 0, 0, 7, 0, 0, 0x7f, 0xff, 0xff, 0xff, 0];
+        assert!(read_from_slice::<Nbt<Blob>>(&input).is_err());
+        // Deeply nested lists
+        let mut input = vec![10, 0, 0, 9, 0, 0];
+        for _ in 0..1000 {
+            input.extend_from_slice(&[9, 0, 0, 0, 1]);
+        }
+        assert!(read_from_slice::<Nbt<Blob>>(&input).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! (De)serialize block positions packed into 64 bits.
+//!
+//! The protocol sends block positions as a single 64-bit integer holding x
+//! and z as 26-bit two's complement integers and y as unsigned 12-bit
+//! integer. Their order changed in 1.14, see `Layout`.
+//!
+//! Since the (de)serializers do not know the protocol version, the layout is
+//! chosen through `Config::layout`. By default, the layout of the newest
+//! versions is used. Other formats see a newtype struct around the position
+//! packed in that default layout.
+//!
+//! Types representing block positions implement `Serialize` and `Deserialize`
+//! through `serialize` and `deserialize`.
+
+use serde::{
+    de::{Error, Visitor},
+    Deserialize, Deserializer, Serializer,
+};
+use std::fmt;
+
+/// The name of the newtype struct the (de)serializers treat as block
+/// position.
+pub(crate) const TOKEN: &str = "$serde_minecraft::position";
+
+/// The order of the coordinates in a packed block position.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Layout {
+    /// x in the highest 26 bits, followed by y and z, used up to 1.13.
+    Xyz,
+    /// x in the highest 26 bits, followed by z and y, used since 1.14.
+    Xzy,
+}
+
+impl Layout {
+    /// Packs the given coordinates, truncating them to their widths.
+    pub fn pack(self, x: i32, y: u16, z: i32) -> u64 {
+        let x = x as u64 & 0x3ff_ffff;
+        let y = u64::from(y) & 0xfff;
+        let z = z as u64 & 0x3ff_ffff;
+
+        match self {
+            Layout::Xyz => x << 38 | y << 26 | z,
+            Layout::Xzy => x << 38 | z << 12 | y,
+        }
+    }
+
+    /// Unpacks the coordinates, sign-extending x and z.
+    pub fn unpack(self, val: u64) -> (i32, u16, i32) {
+        let val = val as i64;
+        let x = (val >> 38) as i32;
+
+        match self {
+            Layout::Xyz => (x, (val >> 26 & 0xfff) as u16, (val << 38 >> 38) as i32),
+            Layout::Xzy => (x, (val & 0xfff) as u16, (val << 26 >> 38) as i32),
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::Xzy
+    }
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<(i32, u16, i32), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(TOKEN, PositionVisitor)
+}
+
+pub fn serialize<S>(x: i32, y: u16, z: i32, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(TOKEN, &Layout::default().pack(x, y, z))
+}
+
+struct PositionVisitor;
+
+impl<'de> Visitor<'de> for PositionVisitor {
+    type Value = (i32, u16, i32);
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a packed block position")
+    }
+
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let val = u64::deserialize(deserializer)?;
+        self.visit_u64(val)
+    }
+
+    fn visit_u64<E: Error>(self, val: u64) -> Result<Self::Value, E> {
+        Ok(Layout::default().unpack(val))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_from_slice, write_to, Config};
+    use bytes::BytesMut;
+    use serde::Serialize;
+
+    #[test]
+    fn layouts() {
+        assert_eq!(Layout::Xyz.pack(1, 2, 3), 1 << 38 | 2 << 26 | 3);
+        assert_eq!(Layout::Xzy.pack(1, 2, 3), 1 << 38 | 3 << 12 | 2);
+
+        for &layout in &[Layout::Xyz, Layout::Xzy] {
+            for &(x, y, z) in &[
+                (0, 0, 0),
+                (1, 2, 3),
+                (-1, 255, -1),
+                (-33_554_432, 4095, 33_554_431),
+                (33_554_431, 0, -33_554_432),
+            ] {
+                assert_eq!(layout.unpack(layout.pack(x, y, z)), (x, y, z));
+            }
+        }
+    }
+
+    #[test]
+    fn sign_extension() {
+        let val = Layout::Xyz.pack(-1, 0, -2);
+        assert_eq!(val, 0xffff_ffc0_03ff_fffe);
+        assert_eq!(Layout::Xyz.unpack(val), (-1, 0, -2));
+
+        let val = Layout::Xzy.pack(-1, 0, -2);
+        assert_eq!(val, 0xffff_ffff_ffff_e000);
+        assert_eq!(Layout::Xzy.unpack(val), (-1, 0, -2));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32, u16, i32);
+
+    impl<'de> Deserialize<'de> for Position {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            let (x, y, z) = deserialize(d)?;
+            Ok(Position(x, y, z))
+        }
+    }
+
+    impl Serialize for Position {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            serialize(self.0, self.1, self.2, s)
+        }
+    }
+
+    #[test]
+    fn selected_layout() {
+        let pos = Position(1, 2, 3);
+
+        let config = Config::default().layout(Layout::Xyz);
+        let mut buf = BytesMut::new();
+        config.write_to(&pos, &mut buf).unwrap();
+        assert_eq!(&buf[..], &[0, 0, 0, 0x40, 0x08, 0, 0, 3]);
+        let read = config.read_from_slice::<Position>(&buf);
+        assert_eq!(read.unwrap(), pos);
+
+        let mut buf = BytesMut::new();
+        write_to(&pos, &mut buf).unwrap();
+        assert_eq!(&buf[..], &[0, 0, 0, 0x40, 0, 0, 0x30, 2]);
+        assert_eq!(read_from_slice::<Position>(&buf).unwrap(), pos);
+    }
+}
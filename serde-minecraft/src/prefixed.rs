@@ -0,0 +1,190 @@
+//! (De)serialize sequences with a fixed-width length prefix.
+//!
+//! Most sequences in the protocol are prefixed with their length as a VarInt,
+//! which is what this library writes for any sequence. Some, however, are
+//! prefixed with an (unsigned) byte, a short or an int instead, e.g. the
+//! properties of the Entity Properties packet. The submodules of this module
+//! write and read such length prefixes. They respect the `Limits` like any
+//! other sequence.
+//!
+//! Fixed-size arrays (e.g. `[u8; 16]`) need no module: like tuples, they are
+//! written without any length prefix.
+//!
+//! Use these modules via e.g. `#[serde(with = "serde_minecraft::prefixed::int")]`.
+
+use serde::{
+    de::{Deserialize, SeqAccess, Visitor},
+    Deserializer, Serialize, Serializer,
+};
+use std::{cmp, fmt, i16, i32, iter::FromIterator, marker::PhantomData, u8};
+
+/// The width of a length prefix.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Prefix {
+    Byte,
+    Short,
+    Int,
+}
+
+impl Prefix {
+    /// Returns the prefix the (de)serializers use for the sequence inside the
+    /// newtype struct of the given name, if any.
+    pub(crate) fn from_token(name: &str) -> Option<Prefix> {
+        match name {
+            BYTE_TOKEN => Some(Prefix::Byte),
+            SHORT_TOKEN => Some(Prefix::Short),
+            INT_TOKEN => Some(Prefix::Int),
+            _ => None,
+        }
+    }
+
+    /// The size of the prefix on the wire.
+    pub(crate) fn size(self) -> usize {
+        match self {
+            Prefix::Byte => 1,
+            Prefix::Short => 2,
+            Prefix::Int => 4,
+        }
+    }
+
+    /// The largest length the prefix can hold.
+    pub(crate) fn max_len(self) -> usize {
+        match self {
+            Prefix::Byte => u8::MAX as usize,
+            Prefix::Short => i16::MAX as usize,
+            Prefix::Int => i32::MAX as usize,
+        }
+    }
+}
+
+const BYTE_TOKEN: &str = "$serde_minecraft::prefixed::byte";
+const SHORT_TOKEN: &str = "$serde_minecraft::prefixed::short";
+const INT_TOKEN: &str = "$serde_minecraft::prefixed::int";
+
+/// Sequences prefixed with their length as an unsigned byte.
+pub mod byte {
+    use super::*;
+
+    pub fn deserialize<'de, I, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        I: Deserialize<'de>,
+        T: FromIterator<I>,
+        D: Deserializer<'de>,
+    {
+        super::deserialize(BYTE_TOKEN, deserializer)
+    }
+
+    pub fn serialize<T, S>(seq: T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: IntoIterator + Copy,
+        T::Item: Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(BYTE_TOKEN, &Elements(seq))
+    }
+}
+
+/// Sequences prefixed with their length as a short.
+pub mod short {
+    use super::*;
+
+    pub fn deserialize<'de, I, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        I: Deserialize<'de>,
+        T: FromIterator<I>,
+        D: Deserializer<'de>,
+    {
+        super::deserialize(SHORT_TOKEN, deserializer)
+    }
+
+    pub fn serialize<T, S>(seq: T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: IntoIterator + Copy,
+        T::Item: Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(SHORT_TOKEN, &Elements(seq))
+    }
+}
+
+/// Sequences prefixed with their length as an int.
+pub mod int {
+    use super::*;
+
+    pub fn deserialize<'de, I, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        I: Deserialize<'de>,
+        T: FromIterator<I>,
+        D: Deserializer<'de>,
+    {
+        super::deserialize(INT_TOKEN, deserializer)
+    }
+
+    pub fn serialize<T, S>(seq: T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: IntoIterator + Copy,
+        T::Item: Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(INT_TOKEN, &Elements(seq))
+    }
+}
+
+fn deserialize<'de, I, T, D>(
+    token: &'static str,
+    deserializer: D,
+) -> Result<T, D::Error>
+where
+    I: Deserialize<'de>,
+    T: FromIterator<I>,
+    D: Deserializer<'de>,
+{
+    let values = deserializer
+        .deserialize_newtype_struct(token, PrefixedVisitor(PhantomData))?;
+    Ok(T::from_iter(values))
+}
+
+struct Elements<T>(T);
+
+impl<T> Serialize for Elements<T>
+where
+    T: IntoIterator + Copy,
+    T::Item: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0)
+    }
+}
+
+struct PrefixedVisitor<I>(PhantomData<I>);
+
+impl<'de, I: Deserialize<'de>> Visitor<'de> for PrefixedVisitor<I> {
+    type Value = Vec<I>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a length-prefixed sequence")
+    }
+
+    // Other formats see a newtype struct around a regular sequence.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Vec<I>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Vec<I>, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        // Don't trust the length blindly when deserializing other formats.
+        let capacity = cmp::min(seq.size_hint().unwrap_or(0), 4096);
+        let mut values = Vec::with_capacity(capacity);
+
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+}
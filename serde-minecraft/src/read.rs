@@ -0,0 +1,96 @@
+//! The sources the deserializer can read from.
+//!
+//! Any `Buf` can be deserialized from, but since its contents need not be
+//! contiguous, strings and byte arrays have to be copied out of it. Slices
+//! allow handing out borrowed strings and byte arrays instead.
+
+use bytes::Buf;
+
+/// A source of bytes for the deserializer.
+pub trait Read<'de>: Buf {
+    /// Reads the next `len` bytes.
+    ///
+    /// Callers must ensure at least `len` bytes remain.
+    fn read_slice<'a>(&'a mut self, len: usize) -> Reference<'de, 'a>;
+}
+
+/// A slice read from a `Read`.
+pub enum Reference<'b, 'c> {
+    /// Borrowed from the input, and thus valid for the whole deserialization.
+    Borrowed(&'b [u8]),
+    /// Copied into a scratch buffer, valid until the next read.
+    Copied(&'c [u8]),
+}
+
+/// Reads from a `Buf`, copying slices into a scratch buffer.
+pub struct BufRead<'b, B> {
+    buf: &'b mut B,
+    scratch: Vec<u8>,
+}
+
+/// Reads from a slice, borrowing slices from it.
+pub struct SliceRead<'de> {
+    slice: &'de [u8],
+}
+
+impl<'b, B> BufRead<'b, B> {
+    pub fn new(buf: &'b mut B) -> Self {
+        BufRead {
+            buf,
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl<'b, B: Buf> Buf for BufRead<'b, B> {
+    fn remaining(&self) -> usize {
+        self.buf.remaining()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.buf.bytes()
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.buf.advance(cnt)
+    }
+}
+
+impl<'de, 'b, B: Buf> Read<'de> for BufRead<'b, B> {
+    fn read_slice<'a>(&'a mut self, len: usize) -> Reference<'de, 'a> {
+        self.scratch.clear();
+        self.scratch.resize(len, 0);
+        self.buf.copy_to_slice(&mut self.scratch);
+
+        Reference::Copied(&self.scratch)
+    }
+}
+
+impl<'de> SliceRead<'de> {
+    pub fn new(slice: &'de [u8]) -> Self {
+        SliceRead { slice }
+    }
+}
+
+impl<'de> Buf for SliceRead<'de> {
+    fn remaining(&self) -> usize {
+        self.slice.len()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.slice
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.slice = &self.slice[cnt..];
+    }
+}
+
+impl<'de> Read<'de> for SliceRead<'de> {
+    fn read_slice<'a>(&'a mut self, len: usize) -> Reference<'de, 'a> {
+        let (head, tail) = self.slice.split_at(len);
+        self.slice = tail;
+
+        Reference::Borrowed(head)
+    }
+}
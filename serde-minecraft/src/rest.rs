@@ -0,0 +1,69 @@
+//! (De)serialize byte arrays extending to the end of the input.
+//!
+//! Some packets, like plugin messages, end with a byte array that is not
+//! length-prefixed but takes up the rest of the packet. This module writes
+//! byte arrays without length prefix and reads all remaining input, so it may
+//! only be used for the last field of a packet.
+//!
+//! Byte arrays are deserialized into `Bytes`, which share the memory of the
+//! input when read through `read_from_bytes` (see `shared_bytes`).
+//!
+//! Use this module via `#[serde(with = "serde_minecraft::rest")]`.
+
+use crate::shared_bytes::{self, BytesVisitor};
+use bytes::Bytes;
+use serde::{
+    de::{Error, Visitor},
+    Deserializer, Serialize, Serializer,
+};
+use std::fmt;
+
+/// The name of the newtype struct the (de)serializers treat as rest of the
+/// input.
+pub(crate) const TOKEN: &str = "$serde_minecraft::rest";
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Bytes, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(TOKEN, RestVisitor)
+}
+
+pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]>,
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(TOKEN, &Raw(bytes.as_ref()))
+}
+
+/// Serializes the contained bytes as byte array.
+pub(crate) struct Raw<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Serialize for Raw<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+struct RestVisitor;
+
+impl<'de> Visitor<'de> for RestVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("the remaining bytes")
+    }
+
+    // Other formats see a newtype struct around a regular byte array.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Bytes, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        shared_bytes::deserialize(deserializer)
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Bytes, E> {
+        BytesVisitor.visit_bytes(v)
+    }
+}
@@ -1,23 +1,46 @@
-use crate::error::Error;
+use crate::{
+    config::Config,
+    error::Error,
+    nbt,
+    position::{self, Layout},
+    prefixed::Prefix,
+    rest,
+};
 use bytes::BufMut;
 use minecraft_varint::{
     var_i32_length, var_i64_length, var_usize_length, VarWriteExt,
 };
 use serde::ser::{self, *};
-use std::{i32, io::ErrorKind};
+use std::i32;
 
 #[derive(Debug)]
-pub struct Serializer<'a, B>(&'a mut B);
+pub struct Serializer<'a, B> {
+    buf: &'a mut B,
+    layout: Layout,
+    /// Whether the next byte array is written without length prefix.
+    raw_bytes: bool,
+    /// Whether the next `u64` is a block position in the default layout,
+    /// which is written in `layout`.
+    position: bool,
+    /// The length prefix of the next sequence, if it is not a VarInt.
+    prefix: Option<Prefix>,
+}
 
 impl<'a, B> Serializer<'a, B> {
-    pub fn new(buf: &'a mut B) -> Self {
-        Serializer(buf)
+    pub fn new(buf: &'a mut B, config: Config) -> Self {
+        Serializer {
+            buf,
+            layout: config.layout,
+            raw_bytes: false,
+            position: false,
+            prefix: None,
+        }
     }
 }
 
 impl<'a, B: BufMut> Serializer<'a, B> {
     fn require_capacity(&self, capacity: usize) -> Result<(), Error> {
-        if self.0.remaining_mut() >= capacity {
+        if self.buf.remaining_mut() >= capacity {
             Ok(())
         } else {
             Err(Error::UnexpectedEndOfBuffer)
@@ -44,88 +67,91 @@ impl<'ser, 'a, B: BufMut> ser::Serializer for &'ser mut Serializer<'a, B> {
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(1)?;
 
-        self.0.put(v);
+        self.buf.put(v);
         Ok(())
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(2)?;
 
-        self.0.put_i16_be(v);
+        self.buf.put_i16_be(v);
         Ok(())
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(var_i32_length(v))?;
 
-        self.0.write_var_i32(v).map_err(|e| match e.kind() {
-            ErrorKind::UnexpectedEof => Error::UnexpectedEndOfBuffer,
-            _ => unreachable!(),
-        })
+        self.buf
+            .write_var_i32(v)
+            .map_err(|_| Error::UnexpectedEndOfBuffer)
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(var_i64_length(v))?;
 
-        self.0.write_var_i64(v).map_err(|e| match e.kind() {
-            ErrorKind::UnexpectedEof => Error::UnexpectedEndOfBuffer,
-            _ => unreachable!(),
-        })
+        self.buf
+            .write_var_i64(v)
+            .map_err(|_| Error::UnexpectedEndOfBuffer)
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(16)?;
 
-        self.0.put_i128_be(v);
+        self.buf.put_i128_be(v);
         Ok(())
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(1)?;
 
-        self.0.put(v);
+        self.buf.put(v);
         Ok(())
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(2)?;
 
-        self.0.put_u16_be(v);
+        self.buf.put_u16_be(v);
         Ok(())
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(4)?;
 
-        self.0.put_u32_be(v);
+        self.buf.put_u32_be(v);
         Ok(())
     }
 
-    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+    fn serialize_u64(self, mut v: u64) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(8)?;
 
-        self.0.put_u64_be(v);
+        if self.position {
+            let (x, y, z) = Layout::default().unpack(v);
+            v = self.layout.pack(x, y, z);
+        }
+
+        self.buf.put_u64_be(v);
         Ok(())
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(16)?;
 
-        self.0.put_u128_be(v);
+        self.buf.put_u128_be(v);
         Ok(())
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(4)?;
 
-        self.0.put_f32_be(v);
+        self.buf.put_f32_be(v);
         Ok(())
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
         self.require_capacity(8)?;
 
-        self.0.put_f64_be(v);
+        self.buf.put_f64_be(v);
         Ok(())
     }
 
@@ -138,23 +164,31 @@ impl<'ser, 'a, B: BufMut> ser::Serializer for &'ser mut Serializer<'a, B> {
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        if self.raw_bytes {
+            self.raw_bytes = false;
+            self.require_capacity(v.len())?;
+            self.buf.put(v);
+            return Ok(());
+        }
+
         self.require_capacity(var_usize_length(v.len()) + v.len())?;
 
         self.serialize_i32(v.len() as i32)?;
-        self.0.put(v);
+        self.buf.put(v);
 
         Ok(())
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        Err(Error::Unsupported)
+        self.serialize_bool(false)
     }
 
-    fn serialize_some<T: ?Sized>(self, _: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
-        Err(Error::Unsupported)
+        self.serialize_bool(true)?;
+        value.serialize(self)
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
@@ -179,26 +213,48 @@ impl<'ser, 'a, B: BufMut> ser::Serializer for &'ser mut Serializer<'a, B> {
 
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
+        if name == rest::TOKEN || name == nbt::TOKEN {
+            self.raw_bytes = true;
+            let res = value.serialize(&mut *self);
+            self.raw_bytes = false;
+            return res;
+        }
+
+        if name == position::TOKEN {
+            self.position = true;
+            let res = value.serialize(&mut *self);
+            self.position = false;
+            return res;
+        }
+
+        if let Some(prefix) = Prefix::from_token(name) {
+            self.prefix = Some(prefix);
+            let res = value.serialize(&mut *self);
+            self.prefix = None;
+            return res;
+        }
+
         value.serialize(self)
     }
 
     fn serialize_newtype_variant<T: ?Sized>(
         self,
         _: &'static str,
-        _: u32,
+        variant_index: u32,
         _: &'static str,
-        _: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
-        Err(Error::Unsupported)
+        (variant_index as i32).serialize(&mut *self)?;
+        value.serialize(self)
     }
 
     fn serialize_seq(
@@ -210,13 +266,26 @@ impl<'ser, 'a, B: BufMut> ser::Serializer for &'ser mut Serializer<'a, B> {
             None => return Err(Error::LengthRequired),
         };
 
-        if len > (i32::MAX as usize) {
+        let prefix = self.prefix.take();
+        let max_len = prefix.map_or(i32::MAX as usize, Prefix::max_len);
+        if len > max_len {
             let msg =
-                "list too long, max contain at max i32::MAX elements".to_owned();
+                format!("list too long, may contain at most {} elements", max_len);
             return Err(Error::Custom(msg));
         }
 
-        self.serialize_i32(len as i32)?;
+        match prefix {
+            Some(prefix) => {
+                self.require_capacity(prefix.size())?;
+                match prefix {
+                    Prefix::Byte => self.buf.put_u8(len as u8),
+                    Prefix::Short => self.buf.put_i16_be(len as i16),
+                    Prefix::Int => self.buf.put_i32_be(len as i32),
+                }
+            }
+            None => self.serialize_i32(len as i32)?,
+        }
+
         Ok(self)
     }
 
@@ -235,11 +304,12 @@ impl<'ser, 'a, B: BufMut> ser::Serializer for &'ser mut Serializer<'a, B> {
     fn serialize_tuple_variant(
         self,
         _: &'static str,
-        _: u32,
+        variant_index: u32,
         _: &'static str,
         _: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Err(Error::Unsupported)
+        (variant_index as i32).serialize(&mut *self)?;
+        Ok(self)
     }
 
     fn serialize_map(
@@ -260,11 +330,12 @@ impl<'ser, 'a, B: BufMut> ser::Serializer for &'ser mut Serializer<'a, B> {
     fn serialize_struct_variant(
         self,
         _: &'static str,
-        _: u32,
+        variant_index: u32,
         _: &'static str,
         _: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        Err(Error::Unsupported)
+        (variant_index as i32).serialize(&mut *self)?;
+        Ok(self)
     }
 
     fn is_human_readable(&self) -> bool {
@@ -326,15 +397,15 @@ impl<'ser, 'a, B: BufMut> ser::SerializeTupleVariant
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, _: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::Unsupported)
+        value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Err(Error::Unsupported)
+        Ok(())
     }
 }
 
@@ -390,15 +461,15 @@ impl<'ser, 'a, B: BufMut> ser::SerializeStructVariant
     fn serialize_field<T>(
         &mut self,
         _: &'static str,
-        _: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::Unsupported)
+        value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Err(Error::Unsupported)
+        Ok(())
     }
 }
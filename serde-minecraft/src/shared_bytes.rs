@@ -0,0 +1,88 @@
+//! (De)serialize `Bytes` as length-prefixed byte arrays.
+//!
+//! When read through `read_from_bytes`, the deserialized `Bytes` share the
+//! memory of the input instead of copying it, like any `Bytes` deserialized
+//! from it. With any other deserializer, the bytes are copied.
+//!
+//! Use this module via `#[serde(with = "serde_minecraft::shared_bytes")]`.
+
+use bytes::Bytes;
+use serde::{
+    de::{Error, Visitor},
+    Deserializer, Serializer,
+};
+use std::{
+    any::TypeId,
+    fmt,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ptr,
+};
+
+/// Whether `T` is `Bytes`.
+pub(crate) fn is_bytes<T>() -> bool {
+    // `TypeId::of` requires `T: 'static`, which visitors' values need not be.
+    // Since type IDs do not depend on lifetimes, the bound is dropped by
+    // calling it through a trait object with the lifetime erased.
+    trait NonStaticAny {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static;
+    }
+
+    impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static,
+        {
+            TypeId::of::<T>()
+        }
+    }
+
+    let phantom = PhantomData::<T>;
+    let any = unsafe {
+        mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&phantom)
+    };
+    any.type_id() == TypeId::of::<Bytes>()
+}
+
+/// Returns `bytes` as `T`, which must be `Bytes`.
+pub(crate) fn cast<T>(bytes: Bytes) -> T {
+    assert!(is_bytes::<T>(), "cast to a type other than Bytes");
+
+    // `T` is `Bytes`, which has no lifetimes that could differ.
+    let bytes = ManuallyDrop::new(bytes);
+    unsafe { ptr::read(&*bytes as *const Bytes as *const T) }
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Bytes, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_bytes(BytesVisitor)
+}
+
+pub fn serialize<S>(bytes: &Bytes, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_bytes(bytes)
+}
+
+pub(crate) struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a byte array")
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Bytes, E> {
+        Ok(Bytes::from(v))
+    }
+
+    fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Bytes, E> {
+        Ok(Bytes::from(v))
+    }
+}
@@ -1,15 +1,23 @@
-use crate::error::Error;
+use crate::{error::Error, nbt, prefixed::Prefix, rest};
 use minecraft_varint::{var_i32_length, var_i64_length, var_usize_length};
 use serde::ser::{self, *};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct ComputeSize {
     size: usize,
+    /// Whether the next byte array is written without length prefix.
+    raw_bytes: bool,
+    /// The length prefix of the next sequence, if it is not a VarInt.
+    prefix: Option<Prefix>,
 }
 
 impl ComputeSize {
     pub fn new() -> Self {
-        ComputeSize { size: 0 }
+        ComputeSize {
+            size: 0,
+            raw_bytes: false,
+            prefix: None,
+        }
     }
 
     pub fn size(&self) -> usize {
@@ -53,6 +61,11 @@ impl<'ser> ser::Serializer for &'ser mut ComputeSize {
         Ok(())
     }
 
+    fn serialize_i128(self, _: i128) -> Result<Self::Ok, Self::Error> {
+        self.size += 16;
+        Ok(())
+    }
+
     fn serialize_u8(self, _: u8) -> Result<Self::Ok, Self::Error> {
         self.size += 1;
         Ok(())
@@ -73,6 +86,11 @@ impl<'ser> ser::Serializer for &'ser mut ComputeSize {
         Ok(())
     }
 
+    fn serialize_u128(self, _: u128) -> Result<Self::Ok, Self::Error> {
+        self.size += 16;
+        Ok(())
+    }
+
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         self.serialize_u32(v.to_bits())
     }
@@ -90,23 +108,30 @@ impl<'ser> ser::Serializer for &'ser mut ComputeSize {
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        self.size += var_usize_length(v.len()) + v.len();
+        if self.raw_bytes {
+            self.raw_bytes = false;
+        } else {
+            self.size += var_usize_length(v.len());
+        }
+
+        self.size += v.len();
         Ok(())
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        Err(Error::Unsupported)
+        self.serialize_bool(false)
     }
 
-    fn serialize_some<T: ?Sized>(self, _: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
-        Err(Error::Unsupported)
+        self.serialize_bool(true)?;
+        value.serialize(self)
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
-        Err(Error::Unsupported)
+        Ok(())
     }
 
     fn serialize_unit_struct(
@@ -122,41 +147,60 @@ impl<'ser> ser::Serializer for &'ser mut ComputeSize {
         variant_index: u32,
         _: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        // MC likes 1-based indices for enums
-        (variant_index as i32 + 1).serialize(self)
+        (variant_index as i32).serialize(self)
     }
 
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
+        if name == rest::TOKEN || name == nbt::TOKEN {
+            self.raw_bytes = true;
+            let res = value.serialize(&mut *self);
+            self.raw_bytes = false;
+            return res;
+        }
+
+        if let Some(prefix) = Prefix::from_token(name) {
+            self.prefix = Some(prefix);
+            let res = value.serialize(&mut *self);
+            self.prefix = None;
+            return res;
+        }
+
         value.serialize(self)
     }
 
     fn serialize_newtype_variant<T: ?Sized>(
         self,
         _: &'static str,
-        _: u32,
+        variant_index: u32,
         _: &'static str,
-        _: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
-        Err(Error::Unsupported)
+        (variant_index as i32).serialize(&mut *self)?;
+        value.serialize(self)
     }
 
     fn serialize_seq(
         self,
         len: Option<usize>,
     ) -> Result<Self::SerializeSeq, Self::Error> {
-        match len {
-            Some(l) => self.serialize_i32(l as i32)?,
+        let len = match len {
+            Some(l) => l,
             None => return Err(Error::LengthRequired),
+        };
+
+        match self.prefix.take() {
+            Some(prefix) => self.size += prefix.size(),
+            None => self.serialize_i32(len as i32)?,
         }
 
         Ok(self)
@@ -177,11 +221,12 @@ impl<'ser> ser::Serializer for &'ser mut ComputeSize {
     fn serialize_tuple_variant(
         self,
         _: &'static str,
-        _: u32,
+        variant_index: u32,
         _: &'static str,
         _: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Err(Error::Unsupported)
+        (variant_index as i32).serialize(&mut *self)?;
+        Ok(self)
     }
 
     fn serialize_map(
@@ -202,11 +247,12 @@ impl<'ser> ser::Serializer for &'ser mut ComputeSize {
     fn serialize_struct_variant(
         self,
         _: &'static str,
-        _: u32,
+        variant_index: u32,
         _: &'static str,
         _: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        Err(Error::Unsupported)
+        (variant_index as i32).serialize(&mut *self)?;
+        Ok(self)
     }
 
     fn is_human_readable(&self) -> bool {
@@ -266,15 +312,15 @@ impl<'ser> ser::SerializeTupleVariant for &'ser mut ComputeSize {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, _: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::Unsupported)
+        value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Err(Error::Unsupported)
+        Ok(())
     }
 }
 
@@ -328,15 +374,15 @@ impl<'ser> ser::SerializeStructVariant for &'ser mut ComputeSize {
     fn serialize_field<T>(
         &mut self,
         _: &'static str,
-        _: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::Unsupported)
+        value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Err(Error::Unsupported)
+        Ok(())
     }
 }
@@ -0,0 +1,131 @@
+use super::{next_packet, play, unexpected_packet, Connection, SessionJoiner};
+use crate::net::{
+    crypto::{self, CryptStream},
+    packets::*,
+    util::Autoflush,
+};
+use futures::prelude::*;
+use log::error;
+use serde_minecraft::bounded::BoundedString;
+use std::{
+    io::{self, Error, ErrorKind},
+    sync::Arc,
+};
+use tokio::{
+    codec::{Framed, FramedParts},
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// The length of the AES key the connection is encrypted with.
+const SHARED_SECRET_LEN: usize = 16;
+
+pub async fn handle(
+    mut conn: Framed<TcpStream, Coder<ClientSide>>,
+    joiner: Option<Arc<dyn SessionJoiner>>,
+    username: String,
+) -> io::Result<Connection> {
+    conn.codec_mut().set_state(ConnectionState::Login);
+
+    let username = BoundedString::new(username)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    conn.send(IncomingPackets::LoginStart(LoginStart { username }))
+        .await?;
+
+    match next_packet(&mut conn).await? {
+        OutgoingPackets::EncryptionRequest(request) => {
+            let mut encrypted_conn = encrypt(conn, joiner, request).await?;
+            let packet = next_packet(&mut encrypted_conn).await?;
+            finish_login(encrypted_conn, packet).await
+        }
+        packet => finish_login(conn, packet).await,
+    }
+}
+
+/// Answers the encryption request of the server and switches to the
+/// encrypted connection.
+async fn encrypt(
+    mut conn: Framed<TcpStream, Coder<ClientSide>>,
+    joiner: Option<Arc<dyn SessionJoiner>>,
+    request: EncryptionRequest,
+) -> io::Result<Framed<Autoflush<CryptStream<TcpStream>>, Coder<ClientSide>>> {
+    let shared_secret: [u8; SHARED_SECRET_LEN] = rand::random();
+
+    // The session server must know about the join before the server asks it
+    // after receiving the encryption response.
+    if let Some(joiner) = joiner {
+        let digest = crypto::server_digest(&request.public_key, &shared_secret);
+        joiner.join(&digest).await?;
+    }
+
+    let enc_response = EncryptionResponse::encrypt_parts(
+        &request.public_key,
+        &shared_secret,
+        &request.verify_token,
+    )
+    .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    conn.send(IncomingPackets::EncryptionResponse(enc_response))
+        .await?;
+
+    let parts = conn.into_parts();
+
+    let mut parts_enc = FramedParts::new(
+        Autoflush::new(CryptStream::new(parts.io, &shared_secret, &shared_secret)),
+        parts.codec,
+    );
+    parts_enc.read_buf = parts.read_buf;
+    parts_enc.write_buf = parts.write_buf;
+
+    Ok(Framed::from_parts(parts_enc))
+}
+
+/// Reads the packets up to the successful login, starting with the given
+/// one, and hands the connection over to the play state.
+async fn finish_login<S>(
+    mut conn: Framed<S, Coder<ClientSide>>,
+    mut packet: OutgoingPackets,
+) -> io::Result<Connection>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    loop {
+        match packet {
+            // Like vanilla, a negative threshold disables compression.
+            OutgoingPackets::SetCompression(SetCompression { threshold }) => {
+                let threshold = if threshold < 0 {
+                    None
+                } else {
+                    Some(threshold as usize)
+                };
+                conn.codec_mut().set_compression_threshold(threshold);
+            }
+            OutgoingPackets::LoginSuccess(login_success) => {
+                let (inc_tx, out_rx, connection) = Connection::new(
+                    login_success.uuid,
+                    login_success.username,
+                    conn.codec().version(),
+                );
+
+                tokio::spawn(async move {
+                    let res = play::handle(conn, inc_tx, out_rx).await;
+
+                    match res {
+                        Ok(mut transport) => {
+                            let _ = AsyncWriteExt::shutdown(&mut transport).await;
+                        }
+                        Err(e) => error!("{:?}", e),
+                    }
+                });
+
+                return Ok(connection);
+            }
+            OutgoingPackets::Disconnect(disconnect) => {
+                let reason = disconnect.reason.to_plain();
+                return Err(Error::new(ErrorKind::ConnectionAborted, reason));
+            }
+            _ => return Err(unexpected_packet()),
+        }
+
+        packet = next_packet(&mut conn).await?;
+    }
+}
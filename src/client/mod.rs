@@ -0,0 +1,500 @@
+//! A headless client, e.g. for bots and integration tests.
+//!
+//! `ClientBuilder` connects to a server either to query its status, like the
+//! server list of the vanilla client, or to log in as a player. The login
+//! handles encryption, joining through the session server and compression,
+//! and hands back a `Connection` exchanging the packets of the play state.
+//! Keep-alives are answered by the connection itself.
+//!
+//! Clients driving the protocol themselves can frame their connection with
+//! the codec returned by `Coder::client`, e.g. through tokio's `Framed`.
+//!
+//! With the `mock-session` feature, `MockSessionServer` stands in for Mojang's
+//! session server, so online-mode logins can be tested without access to the
+//! internet.
+
+use futures::{
+    channel::mpsc::{self, Receiver, Sender},
+    prelude::*,
+};
+use serde_minecraft::bounded::BoundedString;
+use std::{
+    io::{self, Error, ErrorKind},
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    codec::Framed,
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+};
+
+mod login;
+mod play;
+mod session;
+
+pub use self::session::*;
+pub use crate::{
+    game::world::Uuid,
+    net::packets::{
+        clientbound, serverbound, ClientSide, Coder, ConnectionState, Disconnect,
+        EncryptionRequest, EncryptionResponse, Handshake, IncomingPackets,
+        LoginStart, LoginSuccess, NextState, OutgoingPackets, Ping, ProtocolVersion,
+        SetCompression, StatusHandshake, StatusResponse,
+    },
+};
+#[cfg(any(test, feature = "mock-session"))]
+pub use crate::net::MockSessionServer;
+
+/// A player logged in to a server.
+///
+/// The connection is closed when the server disconnects the player or this
+/// is dropped.
+#[derive(Debug)]
+pub struct Connection {
+    id: Uuid,
+    incoming: Receiver<OutgoingPackets>,
+    outgoing: Sender<IncomingPackets>,
+    protocol_version: ProtocolVersion,
+    username: String,
+}
+
+#[derive(Debug)]
+pub struct ClientBuilder {
+    joiner: Option<Arc<dyn SessionJoiner>>,
+    server_addr: Option<SocketAddr>,
+    username: Option<String>,
+    version: ProtocolVersion,
+}
+
+impl Connection {
+    fn new(
+        id: Uuid,
+        username: String,
+        protocol_version: ProtocolVersion,
+    ) -> (
+        Sender<OutgoingPackets>,
+        Receiver<IncomingPackets>,
+        Connection,
+    ) {
+        let (inc_tx, inc_rx) = mpsc::channel(0);
+        let (out_tx, out_rx) = mpsc::channel(0);
+
+        (
+            inc_tx,
+            out_rx,
+            Connection {
+                id,
+                incoming: inc_rx,
+                outgoing: out_tx,
+                protocol_version,
+                username,
+            },
+        )
+    }
+
+    /// The UUID the server assigned to the player.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The packets sent by the server.
+    ///
+    /// The packet types are named from the perspective of the server, so
+    /// these are `OutgoingPackets`. The stream ends when the server closes
+    /// the connection.
+    pub fn incoming(&mut self) -> &mut Receiver<OutgoingPackets> {
+        &mut self.incoming
+    }
+
+    /// Sends packets to the server.
+    pub fn outgoing(&mut self) -> &mut Sender<IncomingPackets> {
+        &mut self.outgoing
+    }
+
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+
+    /// The username the server logged the player in with.
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+}
+
+impl ClientBuilder {
+    /// Creates a builder for a client of the latest supported version.
+    pub fn new() -> Self {
+        ClientBuilder {
+            joiner: None,
+            server_addr: None,
+            username: None,
+            version: ProtocolVersion::LATEST,
+        }
+    }
+
+    pub fn server_addr(mut self, addr: SocketAddr) -> Self {
+        self.server_addr = Some(addr);
+        self
+    }
+
+    /// Sets how the client joins servers in online mode.
+    ///
+    /// Without a joiner, the connection is still encrypted if the server asks
+    /// for it, but the server will fail to verify the player.
+    pub fn session<J: SessionJoiner + 'static>(mut self, joiner: J) -> Self {
+        self.joiner = Some(Arc::new(joiner));
+        self
+    }
+
+    /// Sets the name of the player to log in as.
+    pub fn username(mut self, username: String) -> Self {
+        self.username = Some(username);
+        self
+    }
+
+    pub fn version(mut self, version: ProtocolVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Queries the status of the server.
+    ///
+    /// Returns the status along with the round-trip time of a ping sent
+    /// afterwards.
+    pub async fn status(self) -> io::Result<(StatusResponse, Duration)> {
+        let mut conn = self.connect(NextState::Status).await?;
+        conn.codec_mut().set_state(ConnectionState::Status);
+
+        conn.send(IncomingPackets::StatusHandshake(StatusHandshake::default()))
+            .await?;
+        let response = match next_packet(&mut conn).await? {
+            OutgoingPackets::StatusResponse(response) => response,
+            _ => return Err(unexpected_packet()),
+        };
+
+        let ping = Ping {
+            value: rand::random(),
+        };
+        let sent_at = Instant::now();
+        conn.send(IncomingPackets::Ping(ping)).await?;
+        match next_packet(&mut conn).await? {
+            OutgoingPackets::Ping(pong) if pong == ping => {}
+            _ => return Err(unexpected_packet()),
+        }
+
+        Ok((response, sent_at.elapsed()))
+    }
+
+    /// Logs in to the server and switches to the play state.
+    ///
+    /// Fails with `ErrorKind::ConnectionAborted` if the server disconnects
+    /// the player during the login.
+    pub async fn login(self) -> io::Result<Connection> {
+        let username = self.username.clone().expect("missing username");
+        let conn = self.connect(NextState::Login).await?;
+
+        login::handle(conn, self.joiner, username).await
+    }
+
+    /// Connects to the server and sends the handshake.
+    async fn connect(
+        &self,
+        next_state: NextState,
+    ) -> io::Result<Framed<TcpStream, Coder<ClientSide>>> {
+        let addr = self.server_addr.expect("missing server_addr");
+        let stream = TcpStream::connect(&addr).await?;
+        let mut conn = Framed::new(
+            stream,
+            Coder::client_with_version(ConnectionState::Start, self.version),
+        );
+
+        let server_addr = BoundedString::new(addr.ip().to_string())
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let handshake = Handshake {
+            protocol_version: self.version.number(),
+            server_addr,
+            server_port: addr.port(),
+            next_state,
+        };
+        conn.send(IncomingPackets::Handshake(handshake)).await?;
+
+        Ok(conn)
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads the next packet sent by the server.
+async fn next_packet<S: AsyncRead + AsyncWrite + Unpin>(
+    conn: &mut Framed<S, Coder<ClientSide>>,
+) -> io::Result<OutgoingPackets> {
+    match conn.next().await {
+        Some(packet) => packet,
+        None => Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "reached end of stream",
+        )),
+    }
+}
+
+fn unexpected_packet() -> Error {
+    Error::new(ErrorKind::InvalidData, "received unexpected packet")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::chat::Component,
+        net::{
+            AllowList, Client, MockSessionServer, MojangAuthenticator,
+            OfflineAuthenticator, ServerBuilder, Status, StatusRequest,
+        },
+    };
+    use futures::{
+        future::{self, Either},
+        pin_mut,
+    };
+    use tokio::net::TcpListener;
+
+    /// Binds a listener for a server to a free port on the loopback interface.
+    ///
+    /// The server takes over the listener itself, so no other socket can grab
+    /// the port in between.
+    fn listener() -> (TcpListener, SocketAddr) {
+        let listener = TcpListener::bind(&([127, 0, 0, 1], 0).into()).unwrap();
+        let addr = listener.local_addr().unwrap();
+        (listener, addr)
+    }
+
+    /// Runs the server until the client is done.
+    ///
+    /// The server is polled first, so it listens before the client connects.
+    async fn with_server<F: Future>(server: ServerBuilder, client: F) -> F::Output {
+        let server = server.run();
+        pin_mut!(server, client);
+
+        match future::select(server, client).await {
+            Either::Left((res, _)) => panic!("server stopped: {:?}", res),
+            Either::Right((output, _)) => output,
+        }
+    }
+
+    fn server(listener: TcpListener) -> (ServerBuilder, mpsc::Receiver<Client>) {
+        let (new_player_tx, new_player_rx) = mpsc::channel(0);
+        let (status_request_tx, status_request_rx) = mpsc::channel(0);
+
+        tokio::spawn(status_request_rx.for_each(|request: StatusRequest| {
+            request.respond(Status {
+                players_max: 20,
+                players_online: 1,
+                description: Component::text("A server"),
+                favicon: None,
+            });
+            future::ready(())
+        }));
+
+        let server = ServerBuilder::new()
+            .listener(listener)
+            .new_player(new_player_tx)
+            .status_request(status_request_tx);
+        (server, new_player_rx)
+    }
+
+    #[tokio::test]
+    async fn status() {
+        let (listener, addr) = listener();
+        let (server, _new_players) = server(listener);
+
+        let client = ClientBuilder::new()
+            .server_addr(addr)
+            .version(ProtocolVersion::V404)
+            .status();
+        let (response, _) = with_server(server, client).await.unwrap();
+
+        assert_eq!(response.protocol_version, 404);
+        assert_eq!(response.players_max, 20);
+        assert_eq!(response.players_online, 1);
+        assert_eq!(response.description, Component::text("A server"));
+    }
+
+    #[tokio::test]
+    async fn login_offline_compressed() {
+        let (listener, addr) = listener();
+        let (server, mut new_players) = server(listener);
+        let server = server.online_mode(false).compression_threshold(16);
+
+        let client = async {
+            let mut conn = ClientBuilder::new()
+                .server_addr(addr)
+                .username("Notch".to_owned())
+                .version(ProtocolVersion::V340)
+                .login()
+                .await
+                .unwrap();
+            let mut player = new_players.next().await.unwrap();
+
+            assert_eq!(conn.username(), "Notch");
+            assert_eq!(conn.id(), player.id());
+            assert_eq!(player.protocol_version(), ProtocolVersion::V340);
+
+            let keep_alive = clientbound::KeepAlive { keep_alive_id: 42 };
+            let chat = clientbound::ChatMessage {
+                message: Component::text("Welcome to the server!"),
+                position: clientbound::ChatPosition::Chat,
+            };
+            player
+                .outgoing()
+                .send(OutgoingPackets::KeepAlive(keep_alive))
+                .await
+                .unwrap();
+            player
+                .outgoing()
+                .send(OutgoingPackets::ChatMessage(chat.clone()))
+                .await
+                .unwrap();
+
+            match player.incoming().next().await {
+                Some(IncomingPackets::KeepAlive(reply)) => {
+                    assert_eq!(reply.keep_alive_id, 42);
+                }
+                other => panic!("unexpected packet {:?}", other),
+            }
+            match conn.incoming().next().await {
+                Some(OutgoingPackets::ChatMessage(received)) => {
+                    assert_eq!(received, chat);
+                }
+                other => panic!("unexpected packet {:?}", other),
+            }
+
+            let chat = serverbound::ChatMessage {
+                message: "Hello!".to_owned(),
+            };
+            conn.outgoing()
+                .send(IncomingPackets::ChatMessage(chat))
+                .await
+                .unwrap();
+            match player.incoming().next().await {
+                Some(IncomingPackets::ChatMessage(received)) => {
+                    assert_eq!(received.message, "Hello!");
+                }
+                other => panic!("unexpected packet {:?}", other),
+            }
+        };
+
+        with_server(server, client).await;
+    }
+
+    #[tokio::test]
+    async fn dropping_connection_disconnects() {
+        let (listener, addr) = listener();
+        let (server, mut new_players) = server(listener);
+        let server = server.online_mode(false);
+
+        let client = async {
+            let conn = ClientBuilder::new()
+                .server_addr(addr)
+                .username("Notch".to_owned())
+                .login()
+                .await
+                .unwrap();
+            let mut player = new_players.next().await.unwrap();
+
+            // Answering keep-alives must not keep the connection open.
+            let keep_alive = clientbound::KeepAlive { keep_alive_id: 1 };
+            player
+                .outgoing()
+                .send(OutgoingPackets::KeepAlive(keep_alive))
+                .await
+                .unwrap();
+            match player.incoming().next().await {
+                Some(IncomingPackets::KeepAlive(_)) => {}
+                other => panic!("unexpected packet {:?}", other),
+            }
+
+            drop(conn);
+            assert!(player.incoming().next().await.is_none());
+        };
+
+        with_server(server, client).await;
+    }
+
+    #[tokio::test]
+    async fn login_online() {
+        let session_server = MockSessionServer::start().unwrap();
+        let id = "853c80ef3c3749fdaa49938b674adae6";
+        session_server.register("token", "jeb_", id);
+
+        let (listener, addr) = listener();
+        let (server, mut new_players) = server(listener);
+        let server = server.authenticator(MojangAuthenticator::with_session_server(
+            session_server.has_joined_url(),
+        ));
+
+        let client = async {
+            let conn = ClientBuilder::new()
+                .server_addr(addr)
+                .username("jeb_".to_owned())
+                .session(MojangJoiner::with_session_server(
+                    session_server.join_url(),
+                    "token".to_owned(),
+                    id.parse().unwrap(),
+                ))
+                .login()
+                .await
+                .unwrap();
+            let player = new_players.next().await.unwrap();
+
+            assert_eq!(conn.id(), Uuid(0x853c80ef3c3749fdaa49938b674adae6));
+            assert_eq!(player.id(), conn.id());
+            assert_eq!(player.username(), "jeb_");
+        };
+
+        with_server(server, client).await;
+    }
+
+    #[tokio::test]
+    async fn login_online_without_session() {
+        let session_server = MockSessionServer::start().unwrap();
+
+        let (listener, addr) = listener();
+        let (server, _new_players) = server(listener);
+        let server = server.authenticator(MojangAuthenticator::with_session_server(
+            session_server.has_joined_url(),
+        ));
+
+        let client = ClientBuilder::new()
+            .server_addr(addr)
+            .username("jeb_".to_owned())
+            .login();
+        let err = with_server(server, client).await.unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::ConnectionAborted);
+        assert_eq!(err.to_string(), "Failed to verify username!");
+    }
+
+    #[tokio::test]
+    async fn login_rejected() {
+        let (listener, addr) = listener();
+        let (server, _new_players) = server(listener);
+        let server = server.authenticator(AllowList::new(
+            OfflineAuthenticator,
+            vec!["Notch".to_owned()],
+        ));
+
+        let client = ClientBuilder::new()
+            .server_addr(addr)
+            .username("jeb_".to_owned())
+            .login();
+        let err = with_server(server, client).await.unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::ConnectionAborted);
+        assert_eq!(err.to_string(), "not on allow list");
+    }
+}
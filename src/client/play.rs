@@ -0,0 +1,79 @@
+use crate::net::packets::*;
+use futures::{channel::mpsc, pin_mut, prelude::*, select};
+use std::io::{self, Error, ErrorKind};
+use tokio::{
+    codec::Framed,
+    io::{AsyncRead, AsyncWrite},
+};
+
+pub async fn handle<S: AsyncRead + AsyncWrite + Unpin>(
+    mut conn: Framed<S, Coder<ClientSide>>,
+    mut incoming: mpsc::Sender<OutgoingPackets>,
+    outgoing: mpsc::Receiver<IncomingPackets>,
+) -> io::Result<S> {
+    conn.codec_mut().set_state(ConnectionState::Play);
+
+    let (mut sink, mut stream) = conn.split();
+
+    // Keep-alives are answered here instead of being passed on, with the
+    // replies sent alongside the packets of the player.
+    let (mut replies, replies_rx) = mpsc::channel(0);
+    let (mut outgoing, mut replies_rx) = (outgoing.fuse(), replies_rx.fuse());
+
+    {
+        // Like on the server, either loop ending tears down the connection.
+        // The write loop ends once the player drops its `Connection`, even
+        // though the read loop still holds on to the sender of the replies.
+        let read = async {
+            while let Some(packet) = stream.next().await {
+                let sent = match packet? {
+                    OutgoingPackets::KeepAlive(keep_alive) => {
+                        let reply = serverbound::KeepAlive {
+                            keep_alive_id: keep_alive.keep_alive_id,
+                        };
+                        replies.send(IncomingPackets::KeepAlive(reply)).await
+                    }
+                    packet => incoming.send(packet).await,
+                };
+
+                if sent.is_err() {
+                    break;
+                }
+            }
+
+            Ok::<_, Error>(())
+        };
+        let write = async {
+            loop {
+                let packet = select! {
+                    packet = outgoing.next() => match packet {
+                        Some(packet) => packet,
+                        None => break,
+                    },
+                    reply = replies_rx.next() => match reply {
+                        Some(reply) => reply,
+                        None => break,
+                    },
+                };
+
+                sink.send(packet).await?;
+            }
+
+            Ok::<_, Error>(())
+        };
+
+        let (read, write) = (read.fuse(), write.fuse());
+        pin_mut!(read, write);
+
+        select! {
+            res = read => res?,
+            res = write => res?,
+        }
+    }
+
+    let conn = stream.reunite(sink).map_err(|_| {
+        Error::new(ErrorKind::Other, "failed to reunite connection halves")
+    })?;
+
+    Ok(conn.into_inner())
+}
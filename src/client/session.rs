@@ -0,0 +1,150 @@
+//! Joining servers in online mode.
+//!
+//! Before logging in to a server in online mode, the client has to tell
+//! Mojang's session server that its player is joining the server identified
+//! by the server digest of the encrypted connection. The server then asks the
+//! session server whether the player has joined (see `Authenticator`). This
+//! module makes that step pluggable through the `SessionJoiner` trait.
+
+use crate::{game::world::Uuid, net::blocking::BlockingPool};
+use futures::prelude::*;
+use reqwest::Client as HttpClient;
+use serde_json::json;
+use std::{
+    fmt::Debug,
+    io::{self, Error, ErrorKind},
+    pin::Pin,
+};
+
+/// The URL of the `join` endpoint of Mojang's session server.
+pub const SESSION_JOIN_URL: &str =
+    "https://sessionserver.mojang.com/session/minecraft/join";
+
+/// The number of threads sending requests to the session server.
+const SESSION_WORKERS: usize = 1;
+
+/// The number of joins waiting for a thread, beyond which joins fail right
+/// away.
+const SESSION_QUEUE_LEN: usize = 16;
+
+/// The future returned by `SessionJoiner::join`.
+pub type JoinFuture<'a> = Pin<Box<dyn Future<Output = io::Result<()>> + Send + 'a>>;
+
+/// Announces that the player is joining a server in online mode.
+pub trait SessionJoiner: Debug + Send + Sync {
+    /// Joins the server with the given server digest.
+    ///
+    /// Returns an error if the session server refused the join, in which case
+    /// the login is aborted.
+    fn join<'a>(&'a self, server_digest: &'a str) -> JoinFuture<'a>;
+}
+
+/// Joins servers through Mojang's session server (or a compatible
+/// replacement), like the vanilla client.
+#[derive(Clone, Debug)]
+pub struct MojangJoiner {
+    access_token: String,
+    http_client: HttpClient,
+    pool: BlockingPool,
+    profile: Uuid,
+    session_server: String,
+}
+
+impl MojangJoiner {
+    /// Creates a joiner for the given profile using Mojang's session server.
+    ///
+    /// The access token is the one obtained from Mojang's authentication
+    /// server when logging in to the account owning the profile.
+    pub fn new(access_token: String, profile: Uuid) -> Self {
+        Self::with_session_server(SESSION_JOIN_URL.to_owned(), access_token, profile)
+    }
+
+    /// Creates a joiner for the given profile using the given `join` endpoint.
+    pub fn with_session_server(
+        join_url: String,
+        access_token: String,
+        profile: Uuid,
+    ) -> Self {
+        MojangJoiner {
+            access_token,
+            http_client: HttpClient::new(),
+            pool: BlockingPool::new(SESSION_WORKERS, SESSION_QUEUE_LEN),
+            profile,
+            session_server: join_url,
+        }
+    }
+
+    pub fn profile(&self) -> Uuid {
+        self.profile
+    }
+
+    pub fn session_server(&self) -> &str {
+        &self.session_server
+    }
+}
+
+impl SessionJoiner for MojangJoiner {
+    fn join<'a>(&'a self, server_digest: &'a str) -> JoinFuture<'a> {
+        // reqwest's async client requires a tokio 0.1 runtime, so we use the
+        // blocking client on a bounded pool of threads instead.
+        let request = self.http_client.post(&self.session_server).json(&json!({
+            "accessToken": self.access_token,
+            "selectedProfile": format!("{:032x}", self.profile.0),
+            "serverId": server_digest,
+        }));
+
+        Box::pin(self.pool.run(move || {
+            request
+                .send()
+                .and_then(|resp| resp.error_for_status())
+                .map(|_| ())
+                .map_err(|e| match e.status() {
+                    Some(status) if status.is_client_error() => {
+                        Error::new(ErrorKind::PermissionDenied, e)
+                    }
+                    _ => Error::new(ErrorKind::Other, e),
+                })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{Authenticator, MockSessionServer, MojangAuthenticator};
+
+    const ID: &str = "853c80ef3c3749fdaa49938b674adae6";
+
+    #[tokio::test]
+    async fn mojang_with_mock_session_server() {
+        let session_server = MockSessionServer::start().unwrap();
+        session_server.register("token", "jeb_", ID);
+
+        let joiner = MojangJoiner::with_session_server(
+            session_server.join_url(),
+            "token".to_owned(),
+            ID.parse().unwrap(),
+        );
+        joiner.join("-1234").await.unwrap();
+
+        let auth = MojangAuthenticator::with_session_server(
+            session_server.has_joined_url(),
+        );
+        let identity = auth.authenticate("jeb_", Some("-1234")).await.unwrap();
+        assert_eq!(identity.id, Uuid(0x853c80ef3c3749fdaa49938b674adae6));
+    }
+
+    #[tokio::test]
+    async fn mojang_with_mock_session_server_wrong_token() {
+        let session_server = MockSessionServer::start().unwrap();
+        session_server.register("token", "jeb_", ID);
+
+        let joiner = MojangJoiner::with_session_server(
+            session_server.join_url(),
+            "other token".to_owned(),
+            ID.parse().unwrap(),
+        );
+        let err = joiner.join("-1234").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+}
@@ -0,0 +1,665 @@
+//! Chat components, the JSON text format used for chat messages, disconnect
+//! reasons, server descriptions and more.
+//!
+//! A component consists of its content, a style and children appended to it,
+//! which inherit the style unless they override it. Besides their JSON
+//! representation, components can be converted from and to legacy text,
+//! which formats text by `§` codes.
+//!
+//! Packet fields send components as JSON strings through
+//! `serde_minecraft::json`.
+
+use serde::{
+    de::{self, Deserializer},
+    Deserialize, Serialize,
+};
+use std::mem;
+
+/// The character introducing a formatting code in legacy text.
+const LEGACY_PREFIX: char = '\u{a7}';
+
+/// A chat component.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
+pub struct Component {
+    #[serde(flatten)]
+    pub content: Content,
+    #[serde(flatten)]
+    pub style: Style,
+    /// Components appended to this one, inheriting its style.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<Component>,
+}
+
+/// What a component displays.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Content {
+    /// Plain text.
+    Text { text: String },
+    /// A translation key, with components to fill its placeholders with.
+    Translate {
+        translate: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        with: Vec<Component>,
+    },
+    /// The score of an entity in an objective.
+    Score { score: Score },
+    /// The names of the entities matching a selector, e.g. `@p`.
+    Selector { selector: String },
+    /// The key bound to a control, e.g. `key.jump`.
+    Keybind { keybind: String },
+}
+
+/// The style of a component.
+///
+/// Unset properties are inherited from the parent component.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Style {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underlined: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub obfuscated: Option<bool>,
+    /// Text inserted into the chat input when shift-clicking the component.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insertion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub click_event: Option<ClickEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hover_event: Option<HoverEvent>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Color {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    /// The default color.
+    Reset,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ClickEvent {
+    pub action: ClickAction,
+    pub value: String,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickAction {
+    OpenUrl,
+    OpenFile,
+    RunCommand,
+    SuggestCommand,
+    ChangePage,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct HoverEvent {
+    pub action: HoverAction,
+    pub value: Box<Component>,
+}
+
+// The variants are named like the actions on the wire.
+#[allow(clippy::enum_variant_names)]
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoverAction {
+    ShowText,
+    ShowItem,
+    ShowEntity,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Score {
+    /// The name of the entity, or a selector matching a single one.
+    pub name: String,
+    pub objective: String,
+    /// The value to display instead of the actual score.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+impl Component {
+    fn new(content: Content) -> Self {
+        Component {
+            content,
+            style: Style::default(),
+            extra: Vec::new(),
+        }
+    }
+
+    /// Creates an unstyled text component.
+    pub fn text(text: impl Into<String>) -> Self {
+        Component::new(Content::Text { text: text.into() })
+    }
+
+    /// Creates an unstyled translation component.
+    pub fn translate(key: impl Into<String>, with: Vec<Component>) -> Self {
+        Component::new(Content::Translate {
+            translate: key.into(),
+            with,
+        })
+    }
+
+    /// Creates an unstyled score component.
+    pub fn score(name: impl Into<String>, objective: impl Into<String>) -> Self {
+        Component::new(Content::Score {
+            score: Score {
+                name: name.into(),
+                objective: objective.into(),
+                value: None,
+            },
+        })
+    }
+
+    /// Creates an unstyled selector component.
+    pub fn selector(selector: impl Into<String>) -> Self {
+        Component::new(Content::Selector {
+            selector: selector.into(),
+        })
+    }
+
+    /// Creates an unstyled keybind component.
+    pub fn keybind(keybind: impl Into<String>) -> Self {
+        Component::new(Content::Keybind {
+            keybind: keybind.into(),
+        })
+    }
+
+    /// Converts legacy text formatted by `§` codes to a component.
+    ///
+    /// Unknown codes are kept as text. Like in legacy text, a color code
+    /// resets all formatting before it.
+    pub fn from_legacy(legacy: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut style = Style::default();
+        let mut text = String::new();
+
+        let mut chars = legacy.chars().peekable();
+        while let Some(ch) = chars.next() {
+            let next_style = match chars.peek() {
+                Some(&code) if ch == LEGACY_PREFIX => style.with_legacy_code(code),
+                _ => None,
+            };
+            let next_style = match next_style {
+                Some(next_style) => next_style,
+                None => {
+                    text.push(ch);
+                    continue;
+                }
+            };
+            chars.next();
+
+            if !text.is_empty() {
+                let mut part =
+                    Component::text(mem::replace(&mut text, String::new()));
+                part.style = style;
+                parts.push(part);
+            }
+            style = next_style;
+        }
+
+        if !text.is_empty() || parts.is_empty() {
+            let mut part = Component::text(text);
+            part.style = style;
+            parts.push(part);
+        }
+
+        if parts.len() == 1 {
+            parts.pop().unwrap()
+        } else {
+            Component::text("").extra(parts)
+        }
+    }
+
+    /// Converts the component to legacy text formatted by `§` codes.
+    ///
+    /// Events and insertions are lost. Since the server has no translations,
+    /// translation components are replaced by their key.
+    pub fn to_legacy(&self) -> String {
+        let mut legacy = String::new();
+        self.write_legacy(&Style::default(), &mut Style::default(), &mut legacy);
+        legacy
+    }
+
+    /// Converts the component to plain text, dropping all formatting.
+    ///
+    /// Like in `to_legacy`, translation components are replaced by their key.
+    pub fn to_plain(&self) -> String {
+        let mut plain = self.content.plain().to_owned();
+        for child in &self.extra {
+            plain.push_str(&child.to_plain());
+        }
+        plain
+    }
+
+    fn write_legacy(
+        &self,
+        parent: &Style,
+        current: &mut Style,
+        legacy: &mut String,
+    ) {
+        let style = self.style.inherit(parent);
+        let text = self.content.plain();
+
+        if !text.is_empty() {
+            let format = style.legacy_format();
+            if format != *current {
+                format.write_legacy_codes(current, legacy);
+                *current = format;
+            }
+            legacy.push_str(text);
+        }
+
+        for child in &self.extra {
+            child.write_legacy(&style, current, legacy);
+        }
+    }
+
+    /// Appends the given components as children.
+    pub fn extra(mut self, extra: impl IntoIterator<Item = Component>) -> Self {
+        self.extra.extend(extra);
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.style.bold = Some(bold);
+        self
+    }
+
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.style.italic = Some(italic);
+        self
+    }
+
+    pub fn underlined(mut self, underlined: bool) -> Self {
+        self.style.underlined = Some(underlined);
+        self
+    }
+
+    pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.style.strikethrough = Some(strikethrough);
+        self
+    }
+
+    pub fn obfuscated(mut self, obfuscated: bool) -> Self {
+        self.style.obfuscated = Some(obfuscated);
+        self
+    }
+
+    pub fn insertion(mut self, insertion: impl Into<String>) -> Self {
+        self.style.insertion = Some(insertion.into());
+        self
+    }
+
+    pub fn click_event(
+        mut self,
+        action: ClickAction,
+        value: impl Into<String>,
+    ) -> Self {
+        self.style.click_event = Some(ClickEvent {
+            action,
+            value: value.into(),
+        });
+        self
+    }
+
+    pub fn hover_event(mut self, action: HoverAction, value: Component) -> Self {
+        self.style.hover_event = Some(HoverEvent {
+            action,
+            value: Box::new(value),
+        });
+        self
+    }
+}
+
+impl Content {
+    /// The text displayed for the content, as far as the server knows it.
+    pub fn plain(&self) -> &str {
+        match self {
+            Content::Text { text } => text,
+            Content::Translate { translate, .. } => translate,
+            Content::Score { score } => {
+                score.value.as_ref().map_or("", String::as_str)
+            }
+            Content::Selector { selector } => selector,
+            Content::Keybind { keybind } => keybind,
+        }
+    }
+}
+
+impl Style {
+    /// Returns this style with unset properties taken from `parent`.
+    pub fn inherit(&self, parent: &Style) -> Style {
+        Style {
+            color: self.color.or(parent.color),
+            bold: self.bold.or(parent.bold),
+            italic: self.italic.or(parent.italic),
+            underlined: self.underlined.or(parent.underlined),
+            strikethrough: self.strikethrough.or(parent.strikethrough),
+            obfuscated: self.obfuscated.or(parent.obfuscated),
+            insertion: self.insertion.clone().or_else(|| parent.insertion.clone()),
+            click_event: self
+                .click_event
+                .clone()
+                .or_else(|| parent.click_event.clone()),
+            hover_event: self
+                .hover_event
+                .clone()
+                .or_else(|| parent.hover_event.clone()),
+        }
+    }
+
+    /// Returns the style following the given legacy code, if it is valid.
+    fn with_legacy_code(&self, code: char) -> Option<Style> {
+        let mut style = Style::default();
+
+        match code.to_ascii_lowercase() {
+            'k' => style.obfuscated = Some(true),
+            'l' => style.bold = Some(true),
+            'm' => style.strikethrough = Some(true),
+            'n' => style.underlined = Some(true),
+            'o' => style.italic = Some(true),
+            'r' => return Some(style),
+            code => {
+                style.color = Some(Color::from_legacy_code(code)?);
+                return Some(style);
+            }
+        }
+
+        Some(style.inherit(self))
+    }
+
+    /// Returns the part of the style legacy text can express, normalized.
+    fn legacy_format(&self) -> Style {
+        let flag = |flag: Option<bool>| if flag == Some(true) { flag } else { None };
+
+        Style {
+            color: self.color.filter(|&color| color != Color::Reset),
+            bold: flag(self.bold),
+            italic: flag(self.italic),
+            underlined: flag(self.underlined),
+            strikethrough: flag(self.strikethrough),
+            obfuscated: flag(self.obfuscated),
+            ..Style::default()
+        }
+    }
+
+    /// Writes the codes switching from the `current` legacy format to this
+    /// one.
+    fn write_legacy_codes(&self, current: &Style, legacy: &mut String) {
+        // Color codes reset the formatting, too.
+        let reset = match self.color {
+            Some(color) => Some(color.legacy_code()),
+            None if *current != Style::default() => Some('r'),
+            None => None,
+        };
+        let flags = [
+            (self.obfuscated, 'k'),
+            (self.bold, 'l'),
+            (self.strikethrough, 'm'),
+            (self.underlined, 'n'),
+            (self.italic, 'o'),
+        ];
+
+        if let Some(code) = reset {
+            legacy.push(LEGACY_PREFIX);
+            legacy.push(code);
+        }
+        for &(flag, code) in &flags {
+            if flag == Some(true) {
+                legacy.push(LEGACY_PREFIX);
+                legacy.push(code);
+            }
+        }
+    }
+}
+
+impl Color {
+    /// The legacy formatting code of the color.
+    pub fn legacy_code(self) -> char {
+        match self {
+            Color::Black => '0',
+            Color::DarkBlue => '1',
+            Color::DarkGreen => '2',
+            Color::DarkAqua => '3',
+            Color::DarkRed => '4',
+            Color::DarkPurple => '5',
+            Color::Gold => '6',
+            Color::Gray => '7',
+            Color::DarkGray => '8',
+            Color::Blue => '9',
+            Color::Green => 'a',
+            Color::Aqua => 'b',
+            Color::Red => 'c',
+            Color::LightPurple => 'd',
+            Color::Yellow => 'e',
+            Color::White => 'f',
+            Color::Reset => 'r',
+        }
+    }
+
+    /// Returns the color of the given legacy formatting code, if any.
+    pub fn from_legacy_code(code: char) -> Option<Color> {
+        let color = match code.to_ascii_lowercase() {
+            '0' => Color::Black,
+            '1' => Color::DarkBlue,
+            '2' => Color::DarkGreen,
+            '3' => Color::DarkAqua,
+            '4' => Color::DarkRed,
+            '5' => Color::DarkPurple,
+            '6' => Color::Gold,
+            '7' => Color::Gray,
+            '8' => Color::DarkGray,
+            '9' => Color::Blue,
+            'a' => Color::Green,
+            'b' => Color::Aqua,
+            'c' => Color::Red,
+            'd' => Color::LightPurple,
+            'e' => Color::Yellow,
+            'f' => Color::White,
+            _ => return None,
+        };
+
+        Some(color)
+    }
+}
+
+impl<'de> Deserialize<'de> for Component {
+    /// Deserializes a component from its object form, or from the shorthands
+    /// for text components (a string) and components with children (an array
+    /// of the parent followed by its children).
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Object {
+            #[serde(flatten)]
+            content: Content,
+            #[serde(flatten)]
+            style: Style,
+            #[serde(default)]
+            extra: Vec<Component>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Text(String),
+            List(Vec<Component>),
+            Object(Object),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Text(text) => Ok(Component::text(text)),
+            Repr::List(mut list) => {
+                if list.is_empty() {
+                    return Err(de::Error::invalid_length(
+                        0,
+                        &"at least one component",
+                    ));
+                }
+
+                let extra = list.split_off(1);
+                Ok(list.pop().unwrap().extra(extra))
+            }
+            Repr::Object(object) => Ok(Component {
+                content: object.content,
+                style: object.style,
+                extra: object.extra,
+            }),
+        }
+    }
+}
+
+impl From<&str> for Component {
+    fn from(text: &str) -> Self {
+        Component::text(text)
+    }
+}
+
+impl From<String> for Component {
+    fn from(text: String) -> Self {
+        Component::text(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn json_round_trip() {
+        let component = Component::translate(
+            "chat.type.text",
+            vec![
+                Component::selector("@p")
+                    .click_event(ClickAction::SuggestCommand, "/msg jeb_ "),
+                Component::score("jeb_", "kills").italic(true),
+            ],
+        )
+        .color(Color::DarkAqua)
+        .hover_event(HoverAction::ShowText, Component::keybind("key.jump"))
+        .extra(vec![Component::text("!").bold(false)]);
+
+        let json = serde_json::to_value(&component).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "translate": "chat.type.text",
+                "with": [
+                    {
+                        "selector": "@p",
+                        "clickEvent": {
+                            "action": "suggest_command",
+                            "value": "/msg jeb_ ",
+                        },
+                    },
+                    {
+                        "score": { "name": "jeb_", "objective": "kills" },
+                        "italic": true,
+                    },
+                ],
+                "color": "dark_aqua",
+                "hoverEvent": {
+                    "action": "show_text",
+                    "value": { "keybind": "key.jump" },
+                },
+                "extra": [{ "text": "!", "bold": false }],
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<Component>(json).unwrap(),
+            component
+        );
+    }
+
+    #[test]
+    fn json_shorthands() {
+        let component = serde_json::from_str::<Component>(r#""hi""#).unwrap();
+        assert_eq!(component, Component::text("hi"));
+
+        let component = serde_json::from_str::<Component>(
+            r#"["a", {"text": "b", "color": "red"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            component,
+            Component::text("a").extra(vec![Component::text("b").color(Color::Red)])
+        );
+
+        assert!(serde_json::from_str::<Component>("[]").is_err());
+        assert!(serde_json::from_str::<Component>(r#"{"color": "red"}"#).is_err());
+    }
+
+    #[test]
+    fn from_legacy() {
+        assert_eq!(Component::from_legacy(""), Component::text(""));
+        assert_eq!(Component::from_legacy("plain"), Component::text("plain"));
+        assert_eq!(
+            Component::from_legacy("\u{a7}cred"),
+            Component::text("red").color(Color::Red)
+        );
+        assert_eq!(
+            Component::from_legacy(
+                "a\u{a7}6\u{a7}Lb\u{a7}oc\u{a7}9d\u{a7}re\u{a7}z\u{a7}"
+            ),
+            Component::text("").extra(vec![
+                Component::text("a"),
+                Component::text("b").color(Color::Gold).bold(true),
+                Component::text("c")
+                    .color(Color::Gold)
+                    .bold(true)
+                    .italic(true),
+                Component::text("d").color(Color::Blue),
+                Component::text("e\u{a7}z\u{a7}"),
+            ])
+        );
+    }
+
+    #[test]
+    fn to_legacy() {
+        let component = Component::text("a")
+            .color(Color::Gold)
+            .extra(vec![
+                Component::text("b").bold(true),
+                Component::text("c").bold(true),
+                Component::text("").color(Color::Red),
+                Component::translate("d", Vec::new()).color(Color::Reset),
+            ])
+            .extra(vec![Component::score("jeb_", "kills")]);
+
+        assert_eq!(component.to_legacy(), "\u{a7}6a\u{a7}6\u{a7}lbc\u{a7}rd");
+        assert_eq!(component.to_plain(), "abcd");
+
+        let legacy = "\u{a7}ka\u{a7}2\u{a7}l\u{a7}nb\u{a7}rc";
+        assert_eq!(Component::from_legacy(legacy).to_legacy(), legacy);
+    }
+}
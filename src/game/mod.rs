@@ -2,6 +2,7 @@ use crate::net::{Client, StatusRequest};
 use futures::channel::mpsc::Receiver;
 use std::{io, path::Path};
 
+pub mod chat;
 pub mod world;
 
 #[derive(Debug)]
@@ -1,4 +1,12 @@
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{self, Unexpected, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
 
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub struct BlockPosition {
@@ -25,8 +33,16 @@ pub struct RotationFlipped {
     pub pitch: u8,
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Hash, Eq, PartialEq, Serialize)]
-pub struct Uuid(u128);
+/// A UUID, e.g. of a player.
+///
+/// UUIDs are sent as 16 big-endian bytes on the wire and as hyphenated string
+/// in JSON, from which they are parsed both with and without hyphens.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub struct Uuid(pub u128);
+
+/// The error returned when parsing an invalid UUID.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ParseUuidError;
 
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Vec3x32 {
@@ -49,6 +65,117 @@ pub struct Velocity {
     pub z: i16,
 }
 
+impl<'de> Deserialize<'de> for BlockPosition {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (x, y, z) = serde_minecraft::position::deserialize(deserializer)?;
+        Ok(BlockPosition { x, y, z })
+    }
+}
+
+impl Serialize for BlockPosition {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serde_minecraft::position::serialize(self.x, self.y, self.z, serializer)
+    }
+}
+
+impl Display for Uuid {
+    /// Formats the UUID hyphenated, e.g. `853c80ef-3c37-49fd-aa49-938b674adae6`.
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            self.0 >> 96,
+            self.0 >> 80 & 0xffff,
+            self.0 >> 64 & 0xffff,
+            self.0 >> 48 & 0xffff,
+            self.0 & 0xffff_ffff_ffff,
+        )
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = ParseUuidError;
+
+    /// Parses a hyphenated or undashed UUID, like Mojang's APIs return them.
+    fn from_str(s: &str) -> Result<Self, ParseUuidError> {
+        let hyphenated = match s.len() {
+            32 => false,
+            36 => true,
+            _ => return Err(ParseUuidError),
+        };
+
+        let mut val = 0u128;
+        for (i, c) in s.chars().enumerate() {
+            if hyphenated && (i == 8 || i == 13 || i == 18 || i == 23) {
+                if c != '-' {
+                    return Err(ParseUuidError);
+                }
+                continue;
+            }
+
+            let digit = c.to_digit(16).ok_or(ParseUuidError)?;
+            val = val << 4 | u128::from(digit);
+        }
+
+        Ok(Uuid(val))
+    }
+}
+
+impl<'de> Deserialize<'de> for Uuid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(UuidVisitor)
+        } else {
+            u128::deserialize(deserializer).map(Uuid)
+        }
+    }
+}
+
+impl Serialize for Uuid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+struct UuidVisitor;
+
+impl<'de> Visitor<'de> for UuidVisitor {
+    type Value = Uuid;
+
+    fn expecting(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.write_str("a hyphenated or undashed UUID")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Uuid, E> {
+        v.parse()
+            .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+    }
+}
+
+impl Display for ParseUuidError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.write_str("invalid UUID")
+    }
+}
+
+impl Error for ParseUuidError {}
+
 impl From<i32> for EntityId {
     #[inline]
     fn from(val: i32) -> Self {
@@ -96,3 +223,120 @@ impl From<RotationFlipped> for Rotation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use serde_minecraft::{position::Layout, Config};
+
+    #[test]
+    fn uuid_parse() {
+        let uuid = Uuid(0x853c80ef3c3749fdaa49938b674adae6);
+
+        assert_eq!("853c80ef3c3749fdaa49938b674adae6".parse(), Ok(uuid));
+        assert_eq!("853c80ef-3c37-49fd-aa49-938b674adae6".parse(), Ok(uuid));
+        assert_eq!("853C80EF-3C37-49FD-AA49-938B674ADAE6".parse(), Ok(uuid));
+
+        for invalid in &[
+            "",
+            "853c80ef3c3749fdaa49938b674adae",
+            "+53c80ef3c3749fdaa49938b674adae6",
+            "853c80ef3c3749fdaa49938b674adaeg",
+            "853c80ef-3c3749fd-aa49-938b674adae6",
+            "853c80ef-3c37-49fd-aa49-938b674adae6-",
+            "853c80ef3c3749fdaa49938b674ada\u{e9}",
+            "853c80ef3c3749fdaa49938b674a\u{1f600}",
+        ] {
+            assert_eq!(invalid.parse::<Uuid>(), Err(ParseUuidError));
+        }
+    }
+
+    #[test]
+    fn uuid_display() {
+        assert_eq!(
+            Uuid(0x853c80ef3c3749fdaa49938b674adae6).to_string(),
+            "853c80ef-3c37-49fd-aa49-938b674adae6",
+        );
+        assert_eq!(Uuid(1).to_string(), "00000000-0000-0000-0000-000000000001",);
+    }
+
+    #[test]
+    fn uuid_serde() {
+        let uuid = Uuid(0x00112233445566778899aabbccddeeff);
+
+        let mut buf = BytesMut::new();
+        serde_minecraft::write_to(&uuid, &mut buf).unwrap();
+        assert_eq!(
+            &buf[..],
+            &[
+                0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa,
+                0xbb, 0xcc, 0xdd, 0xee, 0xff,
+            ],
+        );
+        assert_eq!(
+            serde_minecraft::read_from_slice::<Uuid>(&buf).unwrap(),
+            uuid
+        );
+
+        let json = serde_json::to_string(&uuid).unwrap();
+        assert_eq!(json, "\"00112233-4455-6677-8899-aabbccddeeff\"");
+        assert_eq!(serde_json::from_str::<Uuid>(&json).unwrap(), uuid);
+        assert_eq!(
+            serde_json::from_str::<Uuid>("\"00112233445566778899aabbccddeeff\"")
+                .unwrap(),
+            uuid,
+        );
+        assert!(serde_json::from_str::<Uuid>("\"00112233\"").is_err());
+    }
+
+    #[test]
+    fn block_position() {
+        let pos = BlockPosition {
+            x: -3,
+            y: 70,
+            z: 33_554_431,
+        };
+
+        for &layout in &[Layout::Xyz, Layout::Xzy] {
+            let config = Config::default().layout(layout);
+            let mut buf = BytesMut::new();
+            config.write_to(&pos, &mut buf).unwrap();
+            assert_eq!(buf.len(), 8);
+
+            let read = config.read_from_slice(&buf).unwrap();
+            assert_eq!(pos, read);
+        }
+    }
+
+    #[test]
+    fn block_position_sign_extension() {
+        let buf = [0xff; 8];
+
+        let pos: BlockPosition = Config::default()
+            .layout(Layout::Xyz)
+            .read_from_slice(&buf)
+            .unwrap();
+        assert_eq!(
+            pos,
+            BlockPosition {
+                x: -1,
+                y: 4095,
+                z: -1
+            }
+        );
+
+        let pos: BlockPosition = Config::default()
+            .layout(Layout::Xzy)
+            .read_from_slice(&buf)
+            .unwrap();
+        assert_eq!(
+            pos,
+            BlockPosition {
+                x: -1,
+                y: 4095,
+                z: -1
+            }
+        );
+    }
+}
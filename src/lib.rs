@@ -1,9 +1,13 @@
 #![allow(dead_code)]
 
-use self::{game::GameBuilder, net::ServerBuilder};
+use self::{
+    game::{chat::Component, GameBuilder},
+    net::ServerBuilder,
+};
 use futures::{channel::mpsc, prelude::*, try_join};
 use std::{io, path::Path};
 
+pub mod client;
 mod game;
 mod net;
 
@@ -41,7 +45,7 @@ pub async fn run_test() {
             let stats = net::Status {
                 players_max: 100,
                 players_online: 0,
-                description: "Bla bla bla".to_owned(),
+                description: Component::text("Bla bla bla"),
                 favicon: None,
             };
 
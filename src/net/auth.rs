@@ -0,0 +1,351 @@
+//! Authentication of players during login.
+//!
+//! After a client has sent its username (and, if the connection is encrypted,
+//! proven that it knows the shared secret), the server needs to turn the
+//! username into a verified identity. Vanilla servers ask Mojang's session
+//! server whether the player has joined the server before; this module makes
+//! that step pluggable through the `Authenticator` trait.
+
+use super::blocking::BlockingPool;
+use crate::game::world::Uuid;
+use futures::prelude::*;
+use openssl::hash::{hash, MessageDigest};
+use reqwest::Client as HttpClient;
+use serde::Deserialize;
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    io::{self, Error, ErrorKind},
+    pin::Pin,
+};
+
+/// The URL of the `hasJoined` endpoint of Mojang's session server.
+pub const SESSION_SERVER_URL: &str =
+    "https://sessionserver.mojang.com/session/minecraft/hasJoined";
+
+/// The number of threads sending requests to the session server.
+const SESSION_WORKERS: usize = 4;
+
+/// The number of requests to the session server waiting for a thread, beyond
+/// which logins fail right away.
+const SESSION_QUEUE_LEN: usize = 64;
+
+/// The future returned by `Authenticator::authenticate`.
+pub type AuthFuture<'a> =
+    Pin<Box<dyn Future<Output = io::Result<Identity>> + Send + 'a>>;
+
+/// Turns the username a client logs in with into a verified identity.
+pub trait Authenticator: Debug + Send + Sync {
+    /// Whether the connection must be encrypted before authenticating.
+    ///
+    /// If this is `true`, `authenticate` is passed the server digest of the
+    /// encrypted connection, which the client has also sent to the session
+    /// server when joining.
+    fn requires_encryption(&self) -> bool;
+
+    /// Verifies the identity of the player with the given username.
+    ///
+    /// Returns an error if the player could not be authenticated, in which
+    /// case the login is aborted.
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        server_digest: Option<&'a str>,
+    ) -> AuthFuture<'a>;
+}
+
+/// The verified identity of a player.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Identity {
+    pub id: Uuid,
+    #[serde(rename = "name")]
+    pub username: String,
+    pub properties: Vec<Property>,
+}
+
+/// A property of a player's profile, e.g. their skin.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub value: String,
+    pub signature: Option<String>,
+}
+
+/// Authenticates players against Mojang's session server (or a compatible
+/// replacement), like a vanilla server in online mode.
+#[derive(Clone, Debug)]
+pub struct MojangAuthenticator {
+    http_client: HttpClient,
+    pool: BlockingPool,
+    session_server: String,
+}
+
+/// Accepts every player under the name they claim, like a vanilla server in
+/// offline mode.
+///
+/// The connection is not encrypted and players are assigned the same UUID a
+/// vanilla server would give them.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OfflineAuthenticator;
+
+/// Only lets the players with the given usernames log in and delegates their
+/// authentication to another authenticator.
+///
+/// Like the vanilla whitelist, usernames are compared ignoring case.
+#[derive(Clone, Debug)]
+pub struct AllowList<A> {
+    allowed: HashSet<String>,
+    inner: A,
+}
+
+impl Identity {
+    /// The signed property containing the player's skin and cape.
+    pub fn textures(&self) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == "textures")
+    }
+}
+
+impl MojangAuthenticator {
+    /// Creates an authenticator using Mojang's session server.
+    pub fn new() -> Self {
+        Self::with_session_server(SESSION_SERVER_URL.to_owned())
+    }
+
+    /// Creates an authenticator using the given `hasJoined` endpoint.
+    pub fn with_session_server(has_joined_url: String) -> Self {
+        MojangAuthenticator {
+            http_client: HttpClient::new(),
+            pool: BlockingPool::new(SESSION_WORKERS, SESSION_QUEUE_LEN),
+            session_server: has_joined_url,
+        }
+    }
+
+    pub fn session_server(&self) -> &str {
+        &self.session_server
+    }
+
+    async fn fetch(&self, username: &str, digest: &str) -> io::Result<Identity> {
+        // reqwest's async client requires a tokio 0.1 runtime, so we use the
+        // blocking client on a bounded pool of threads instead.
+        let request = self
+            .http_client
+            .get(&self.session_server)
+            .query(&[("username", username), ("serverId", digest)]);
+
+        self.pool
+            .run(move || {
+                request
+                    .send()
+                    .and_then(|resp| resp.error_for_status())
+                    .map_err(|e| Error::new(ErrorKind::Other, e))
+                    .and_then(|mut resp| {
+                        resp.json::<Identity>()
+                            .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+                    })
+            })
+            .await
+    }
+}
+
+impl Default for MojangAuthenticator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Authenticator for MojangAuthenticator {
+    fn requires_encryption(&self) -> bool {
+        true
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        server_digest: Option<&'a str>,
+    ) -> AuthFuture<'a> {
+        Box::pin(async move {
+            let digest = server_digest.ok_or_else(|| {
+                Error::new(ErrorKind::InvalidInput, "missing server digest")
+            })?;
+
+            self.fetch(username, digest).await
+        })
+    }
+}
+
+impl Authenticator for OfflineAuthenticator {
+    fn requires_encryption(&self) -> bool {
+        false
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        _server_digest: Option<&'a str>,
+    ) -> AuthFuture<'a> {
+        let identity = Identity {
+            id: offline_player_id(username),
+            username: username.to_owned(),
+            properties: Vec::new(),
+        };
+
+        Box::pin(future::ready(Ok(identity)))
+    }
+}
+
+impl<A> AllowList<A> {
+    pub fn new<I: IntoIterator<Item = String>>(inner: A, allowed: I) -> Self {
+        AllowList {
+            allowed: allowed
+                .into_iter()
+                .map(|username| username.to_ascii_lowercase())
+                .collect(),
+            inner,
+        }
+    }
+
+    pub fn allow(&mut self, username: String) {
+        self.allowed.insert(username.to_ascii_lowercase());
+    }
+
+    pub fn is_allowed(&self, username: &str) -> bool {
+        self.allowed.contains(&username.to_ascii_lowercase())
+    }
+}
+
+impl<A: Authenticator> Authenticator for AllowList<A> {
+    fn requires_encryption(&self) -> bool {
+        self.inner.requires_encryption()
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        server_digest: Option<&'a str>,
+    ) -> AuthFuture<'a> {
+        if !self.is_allowed(username) {
+            let err = Error::new(ErrorKind::PermissionDenied, "not on allow list");
+            return Box::pin(future::ready(Err(err)));
+        }
+
+        self.inner.authenticate(username, server_digest)
+    }
+}
+
+/// Derives the UUID of a player on a server in offline mode.
+///
+/// Like vanilla, this is a name-based (version 3) UUID of the string
+/// `OfflinePlayer:<username>`.
+fn offline_player_id(username: &str) -> Uuid {
+    let name = format!("OfflinePlayer:{}", username);
+    let mut digest = hash(MessageDigest::md5(), name.as_bytes())
+        .expect("failed to compute MD5 hash");
+
+    // Set the version (3) and the IETF variant
+    digest[6] = (digest[6] & 0x0f) | 0x30;
+    digest[8] = (digest[8] & 0x3f) | 0x80;
+
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&digest);
+    Uuid(u128::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::MockSessionServer;
+
+    #[test]
+    fn identity_from_json() {
+        let json = r#"{
+            "id": "853c80ef3c3749fdaa49938b674adae6",
+            "name": "jeb_",
+            "properties": []
+        }"#;
+        let identity = serde_json::from_str::<Identity>(json).unwrap();
+        assert_eq!(identity.id, Uuid(0x853c80ef3c3749fdaa49938b674adae6));
+
+        let json = r#"{"id": "+53c80ef3c3749fdaa49938b674adae6", "name": "", "properties": []}"#;
+        assert!(serde_json::from_str::<Identity>(json).is_err());
+    }
+
+    #[test]
+    fn identity_textures() {
+        let textures = Property {
+            name: "textures".to_owned(),
+            value: "e30=".to_owned(),
+            signature: Some("c2lnbmF0dXJl".to_owned()),
+        };
+        let identity = Identity {
+            id: Uuid(0x853c80ef3c3749fdaa49938b674adae6),
+            username: "jeb_".to_owned(),
+            properties: vec![textures.clone()],
+        };
+
+        assert_eq!(identity.textures(), Some(&textures));
+    }
+
+    #[tokio::test]
+    async fn offline_notch() {
+        let identity = OfflineAuthenticator
+            .authenticate("Notch", None)
+            .await
+            .unwrap();
+
+        assert_eq!(identity.username, "Notch");
+        assert_eq!(
+            identity.id.to_string(),
+            "b50ad385-829d-3141-a216-7e7d7539ba7f"
+        );
+    }
+
+    #[tokio::test]
+    async fn mojang_with_mock_session_server() {
+        let session_server = MockSessionServer::start().unwrap();
+        session_server.join("jeb_", "853c80ef3c3749fdaa49938b674adae6", "-1234");
+
+        let auth = MojangAuthenticator::with_session_server(
+            session_server.has_joined_url(),
+        );
+        let identity = auth.authenticate("jeb_", Some("-1234")).await.unwrap();
+
+        assert_eq!(identity.username, "jeb_");
+        assert_eq!(identity.id, Uuid(0x853c80ef3c3749fdaa49938b674adae6));
+    }
+
+    #[tokio::test]
+    async fn mojang_with_mock_session_server_wrong_digest() {
+        let session_server = MockSessionServer::start().unwrap();
+        session_server.join("jeb_", "853c80ef3c3749fdaa49938b674adae6", "-1234");
+
+        let auth = MojangAuthenticator::with_session_server(
+            session_server.has_joined_url(),
+        );
+
+        assert!(auth.authenticate("jeb_", Some("5678")).await.is_err());
+        assert!(auth.authenticate("jeb_", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn allow_list() {
+        let auth = AllowList::new(OfflineAuthenticator, vec!["Notch".to_owned()]);
+
+        assert!(!auth.requires_encryption());
+        assert!(auth.authenticate("Notch", None).await.is_ok());
+
+        let err = auth.authenticate("jeb_", None).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn allow_list_ignores_case() {
+        let mut auth =
+            AllowList::new(OfflineAuthenticator, vec!["Notch".to_owned()]);
+        auth.allow("JEB_".to_owned());
+
+        assert!(auth.is_allowed("notch"));
+        assert!(auth.is_allowed("NOTCH"));
+        assert!(auth.is_allowed("jeb_"));
+        assert!(!auth.is_allowed("Dinnerbone"));
+    }
+}
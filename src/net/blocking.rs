@@ -0,0 +1,151 @@
+//! A small, fixed pool of threads for blocking calls.
+
+use futures::{channel::oneshot, prelude::*};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, Error, ErrorKind},
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
+        Arc, Mutex, Once,
+    },
+    thread,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Runs blocking calls, e.g. requests of reqwest's blocking client, on a fixed
+/// number of threads.
+///
+/// At most `workers + queue` calls are pending at once. Further calls fail
+/// right away instead of piling up, so peers cannot make the server do an
+/// unbounded amount of blocking work. The threads are spawned on the first
+/// call, so unused pools cost none, and exit once all clones of the pool are
+/// dropped.
+#[derive(Clone)]
+pub struct BlockingPool {
+    jobs: SyncSender<Job>,
+    workers: Arc<Workers>,
+}
+
+/// The threads of a pool, spawned once.
+struct Workers {
+    count: usize,
+    jobs: Arc<Mutex<Receiver<Job>>>,
+    spawned: Once,
+}
+
+impl BlockingPool {
+    pub fn new(workers: usize, queue: usize) -> Self {
+        let (tx, rx) = sync_channel(queue);
+        let workers = Workers {
+            count: workers,
+            jobs: Arc::new(Mutex::new(rx)),
+            spawned: Once::new(),
+        };
+
+        BlockingPool {
+            jobs: tx,
+            workers: Arc::new(workers),
+        }
+    }
+
+    /// Runs the call on one of the threads of the pool.
+    ///
+    /// Fails with `ErrorKind::Other` if too many calls are pending already.
+    pub fn run<T, F>(&self, call: F) -> impl Future<Output = io::Result<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> io::Result<T> + Send + 'static,
+    {
+        self.workers.spawn();
+
+        let (tx, rx) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            let _ = tx.send(call());
+        });
+        let queued = self.jobs.try_send(job).map_err(|e| match e {
+            TrySendError::Full(_) => {
+                Error::new(ErrorKind::Other, "too many blocking calls pending")
+            }
+            TrySendError::Disconnected(_) => {
+                Error::new(ErrorKind::Other, "blocking pool shut down")
+            }
+        });
+
+        async move {
+            queued?;
+            rx.await
+                .map_err(|_| Error::new(ErrorKind::Other, "blocking call aborted"))?
+        }
+    }
+}
+
+impl Debug for BlockingPool {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("BlockingPool").finish()
+    }
+}
+
+impl Workers {
+    fn spawn(&self) {
+        self.spawned.call_once(|| {
+            for _ in 0..self.count {
+                let jobs = self.jobs.clone();
+                thread::spawn(move || work(&jobs));
+            }
+        });
+    }
+}
+
+fn work(jobs: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = match jobs.lock().expect("poisoned lock").recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+
+        job();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn spawns_on_first_call() {
+        let pool = BlockingPool::new(2, 1);
+        // Each thread holds a reference to the queue.
+        assert_eq!(Arc::strong_count(&pool.workers.jobs), 1);
+
+        assert_eq!(block_on(pool.run(|| Ok(1))).unwrap(), 1);
+        assert_eq!(Arc::strong_count(&pool.workers.jobs), 3);
+
+        assert_eq!(block_on(pool.run(|| Ok(2))).unwrap(), 2);
+        assert_eq!(Arc::strong_count(&pool.workers.jobs), 3);
+    }
+
+    #[test]
+    fn rejects_when_full() {
+        let pool = BlockingPool::new(1, 1);
+        let (started_tx, started_rx) = channel();
+        let (gate_tx, gate_rx) = channel::<()>();
+
+        let first = pool.run(move || {
+            started_tx.send(()).unwrap();
+            gate_rx.recv().unwrap();
+            Ok(1)
+        });
+        started_rx.recv().unwrap();
+
+        let second = pool.run(|| Ok(2));
+        let third = pool.run(|| Ok(3));
+        assert_eq!(block_on(third).unwrap_err().kind(), ErrorKind::Other);
+
+        gate_tx.send(()).unwrap();
+        assert_eq!(block_on(first).unwrap(), 1);
+        assert_eq!(block_on(second).unwrap(), 2);
+    }
+}
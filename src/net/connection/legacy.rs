@@ -0,0 +1,153 @@
+//! The server list ping of clients before 1.7.
+//!
+//! Clients from 1.4 to 1.6 open the connection with `0xFE 0x01` (1.6 follows
+//! up with a plugin message we do not need to look at) and expect a kick
+//! packet (`0xFF`) whose reason encodes the status of the server.
+//!
+//! Clients before 1.4 send nothing but `0xFE`, so a ping without a payload
+//! byte within `PAYLOAD_TIMEOUT` is answered in their older format.
+
+use crate::net::{packets::ProtocolVersion, ServerState, Status, StatusRequest};
+use std::{
+    io::{self, Error, ErrorKind},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    timer::Timeout,
+};
+
+/// The first byte of a legacy server list ping.
+pub const PING_ID: u8 = 0xFE;
+
+/// The ID of the kick packet carrying the response.
+const KICK_ID: u8 = 0xFF;
+
+/// The protocol version sent to legacy clients.
+///
+/// Like vanilla, this is a version no legacy client speaks, so they show the
+/// server as incompatible.
+const PROTOCOL_VERSION: u16 = 127;
+
+/// How long to wait for the payload byte sent by clients from 1.4 on.
+const PAYLOAD_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Answers a legacy server list ping whose first byte has already been read.
+pub async fn handle(
+    mut conn: TcpStream,
+    state: ServerState,
+) -> io::Result<TcpStream> {
+    let mut payload = [0; 1];
+    let has_payload =
+        match Timeout::new(conn.read_exact(&mut payload), PAYLOAD_TIMEOUT).await {
+            Ok(read) => {
+                read?;
+                true
+            }
+            Err(_) => false,
+        };
+    if has_payload && payload[0] != 0x01 {
+        return Err(Error::new(ErrorKind::InvalidData, "invalid legacy ping"));
+    }
+
+    let stats = StatusRequest::send_via(state.status_request)
+        .await
+        .ok_or_else(|| Error::new(ErrorKind::Other, "game disconnected"))?;
+    let response = if has_payload {
+        kick_response(&stats)
+    } else {
+        beta_kick_response(&stats)
+    };
+    conn.write_all(&response).await?;
+
+    Ok(conn)
+}
+
+/// Builds the kick packet answering a legacy ping of clients from 1.4 on.
+fn kick_response(stats: &Status) -> Vec<u8> {
+    let latest = ProtocolVersion::LATEST;
+    let reason = format!(
+        "\u{a7}1\0{}\0{}\0{}\0{}\0{}",
+        PROTOCOL_VERSION,
+        latest.name(),
+        stats.description.to_legacy(),
+        stats.players_online,
+        stats.players_max,
+    );
+    kick_packet(&reason)
+}
+
+/// Builds the kick packet answering a legacy ping of clients before 1.4.
+///
+/// Since `§` separates the fields, the description is sent without
+/// formatting.
+fn beta_kick_response(stats: &Status) -> Vec<u8> {
+    let description = stats.description.to_plain().replace('\u{a7}', "");
+    let reason = format!(
+        "{}\u{a7}{}\u{a7}{}",
+        description, stats.players_online, stats.players_max,
+    );
+    kick_packet(&reason)
+}
+
+fn kick_packet(reason: &str) -> Vec<u8> {
+    let mut reason = reason.encode_utf16().collect::<Vec<_>>();
+    reason.truncate(u16::max_value() as usize);
+
+    let mut packet = Vec::with_capacity(3 + 2 * reason.len());
+    packet.push(KICK_ID);
+    packet.extend_from_slice(&(reason.len() as u16).to_be_bytes());
+    for unit in reason {
+        packet.extend_from_slice(&unit.to_be_bytes());
+    }
+
+    packet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::chat::Component;
+
+    fn stats() -> Status {
+        Status {
+            players_max: 20,
+            players_online: 3,
+            description: Component::text("A Minecraft Server"),
+            favicon: None,
+        }
+    }
+
+    fn decode_kick(response: &[u8]) -> String {
+        assert_eq!(response[0], 0xFF);
+
+        let decoded = response[3..]
+            .chunks(2)
+            .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            u16::from_be_bytes([response[1], response[2]]) as usize,
+            decoded.len(),
+        );
+        String::from_utf16(&decoded).unwrap()
+    }
+
+    #[test]
+    fn kick_response_encoding() {
+        let response = kick_response(&stats());
+
+        let expected = "\u{a7}1\0127\01.14.4\0A Minecraft Server\03\020";
+        assert_eq!(decode_kick(&response), expected);
+    }
+
+    #[test]
+    fn beta_kick_response_encoding() {
+        let mut stats = stats();
+        stats.description = Component::from_legacy("A \u{a7}6Minecraft Server");
+        let response = beta_kick_response(&stats);
+
+        let expected = "A Minecraft Server\u{a7}3\u{a7}20";
+        assert_eq!(decode_kick(&response), expected);
+    }
+}
@@ -1,81 +1,75 @@
-use super::{play, ConnectionState};
+use super::{disconnect, play, ConnectionState, Peer};
 use crate::net::{
     crypto::{self, CryptStream},
     packets::*,
     util::Autoflush,
-    Client, ServerState,
+    Client, Identity, ServerState,
 };
-use futures::{compat::Future01CompatExt, prelude::*};
+use futures::prelude::*;
 use log::error;
 use rand;
-use reqwest::r#async::Client as HttpClient;
-use serde::Deserialize;
 use std::io::{self, Error, ErrorKind};
 use tokio::{
     codec::{Framed, FramedParts},
+    io::{AsyncRead, AsyncWrite},
     net::TcpStream,
 };
 
-const SESSION_SERVER_URL: &str =
-    "https://sessionserver.mojang.com/session/minecraft/hasJoined";
+/// The length of the AES key the client encrypts the connection with.
+const SHARED_SECRET_LEN: usize = 16;
 
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-struct ClientValidation {
-    id: String,
-    name: String,
-    properties: Vec<Property>,
-}
-
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-struct Property {
-    name: String,
-    value: String,
-    signature: String,
-}
+/// The length of the verify token sent in the encryption request.
+const VERIFY_TOKEN_LEN: usize = 16;
 
 pub async fn handle(
     mut conn: Framed<TcpStream, Coder>,
-    mut state: ServerState,
+    state: ServerState,
+    peer: Peer,
 ) -> io::Result<TcpStream> {
     conn.codec_mut().set_state(ConnectionState::Login);
 
-    let username = expect_packet!(conn, LoginStart).username;
+    let username = match read_packet!(conn, LoginStart) {
+        Ok(packet) => packet.username.into_inner(),
+        Err(e) => return reject(conn, e).await,
+    };
+
+    if state.authenticator.requires_encryption() {
+        handle_encrypted(conn, state, peer, username).await
+    } else {
+        authenticate(conn, state, peer, &username, None).await
+    }
+}
 
-    let verify_token = rand::random();
+async fn handle_encrypted(
+    mut conn: Framed<TcpStream, Coder>,
+    state: ServerState,
+    peer: Peer,
+    username: String,
+) -> io::Result<TcpStream> {
+    let verify_token: [u8; VERIFY_TOKEN_LEN] = rand::random();
     let enc_request = EncryptionRequest {
         server_id: String::new(),
         public_key: state.keypair.public.clone(),
-        verify_token,
+        verify_token: verify_token.to_vec(),
     };
     conn.send(OutgoingPackets::EncryptionRequest(enc_request))
         .await?;
 
-    let decrypted = expect_packet!(conn, EncryptionResponse)
+    let enc_response = match read_packet!(conn, EncryptionResponse) {
+        Ok(packet) => packet,
+        Err(e) => return reject(conn, e).await,
+    };
+
+    // Without the shared secret there is no way to tell the client what went
+    // wrong, since it already expects the connection to be encrypted.
+    let decrypted = enc_response
         .decrypt_parts(&state.keypair.private)
         .map_err(|e| Error::new(ErrorKind::Other, e))?;
-
-    if decrypted.verify_token != verify_token {
-        error!("invalid verify token");
-        return Ok(conn.into_inner());
-    }
-
-    let login_digest =
-        crypto::server_digest(&state.keypair.public, &decrypted.shared_secret);
-    let validation =
-        ClientValidation::fetch(&state.http_client, &username, &login_digest)
-            .await?;
-
-    if validation.username() != username {
-        error!("invalid username");
-        return Ok(conn.into_inner());
+    if decrypted.shared_secret.len() != SHARED_SECRET_LEN {
+        return Err(Error::new(ErrorKind::InvalidData, "invalid shared secret"));
     }
 
-    let login_success = LoginSuccess {
-        uuid: validation.id_for_client(),
-        username: username.clone(),
-    };
-
-    let mut encrypted_conn = {
+    let encrypted_conn = {
         let parts = conn.into_parts();
 
         let mut parts_enc = FramedParts::new(
@@ -91,76 +85,99 @@ pub async fn handle(
 
         Framed::from_parts(parts_enc)
     };
-    encrypted_conn
-        .send(OutgoingPackets::LoginSuccess(login_success))
-        .await?;
 
-    let (inc_tx, out_rx, client) = Client::new(username);
-    state
-        .new_client
-        .send(client)
-        .await
-        .map_err(|_| Error::new(ErrorKind::Other, "game disconnected"))?;
+    let stream = if decrypted.verify_token[..] != verify_token[..] {
+        error!("invalid verify token");
+        disconnect(encrypted_conn, "Invalid verify token").await?
+    } else {
+        let login_digest =
+            crypto::server_digest(&state.keypair.public, &decrypted.shared_secret);
+        authenticate(encrypted_conn, state, peer, &username, Some(&login_digest))
+            .await?
+    };
 
-    play::handle(encrypted_conn, state, inc_tx, out_rx).await
+    Ok(stream.into_inner().into_inner())
 }
 
-impl ClientValidation {
-    pub async fn fetch(
-        via: &HttpClient,
-        username: &str,
-        digest: &str,
-    ) -> Result<Self, Error> {
-        let mut response = via
-            .get(SESSION_SERVER_URL)
-            .query(&[("username", username), ("serverId", digest)])
-            .send()
-            .compat()
-            .await
-            .and_then(|resp| resp.error_for_status())
-            .map_err(|e| Error::new(ErrorKind::Other, e))?;
-
-        response
-            .json::<ClientValidation>()
-            .compat()
-            .await
-            .map_err(|e| Error::new(ErrorKind::InvalidData, e))
-    }
+/// Verifies the identity of the player and completes the login if successful.
+async fn authenticate<S: AsyncRead + AsyncWrite + Unpin>(
+    conn: Framed<S, Coder>,
+    state: ServerState,
+    peer: Peer,
+    username: &str,
+    server_digest: Option<&str>,
+) -> io::Result<S> {
+    let identity = match state
+        .authenticator
+        .authenticate(username, server_digest)
+        .await
+    {
+        Ok(identity) => identity,
+        Err(ref e) if e.kind() == ErrorKind::PermissionDenied => {
+            return disconnect(conn, &e.to_string()).await;
+        }
+        Err(e) => {
+            error!("failed to authenticate {}: {}", username, e);
+            return disconnect(conn, "Failed to verify username!").await;
+        }
+    };
 
-    pub fn id(&self) -> &str {
-        &self.id
+    if identity.username != username {
+        error!("invalid identity for {}: {:?}", username, identity);
+        return disconnect(conn, "Failed to verify username!").await;
     }
 
-    pub fn id_for_client(&self) -> String {
-        assert_eq!(self.id.len(), 32);
-
-        format!(
-            "{}-{}-{}-{}-{}",
-            &self.id[..8],
-            &self.id[8..12],
-            &self.id[12..16],
-            &self.id[16..20],
-            &self.id[20..],
-        )
-    }
+    finish_login(conn, state, peer, identity).await
+}
 
-    pub fn username(&self) -> &str {
-        &self.name
+/// Disconnects a client that has sent an invalid packet.
+///
+/// Errors not caused by the client are passed through as is.
+async fn reject<S: AsyncRead + AsyncWrite + Unpin>(
+    conn: Framed<S, Coder>,
+    error: io::Error,
+) -> io::Result<S> {
+    if error.kind() != ErrorKind::InvalidData {
+        return Err(error);
     }
+
+    error!("invalid packet during login: {}", error);
+    disconnect(conn, &format!("Internal Exception: {}", error)).await
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Completes the login of an authenticated player and hands the connection
+/// over to the play state.
+async fn finish_login<S: AsyncRead + AsyncWrite + Unpin>(
+    mut conn: Framed<S, Coder>,
+    mut state: ServerState,
+    peer: Peer,
+    identity: Identity,
+) -> io::Result<S> {
+    let id = identity.id;
+    let textures = identity.textures().cloned();
+
+    if let Some(threshold) = state.compression_threshold {
+        conn.send(OutgoingPackets::SetCompression(SetCompression {
+            threshold: threshold as i32,
+        }))
+        .await?;
+        conn.codec_mut().set_compression_threshold(Some(threshold));
+    }
 
-    #[test]
-    fn server_response_id_for_client() {
-        let resp = ClientValidation {
-            id: "11111111222233334444555555555555".to_owned(),
-            name: String::new(),
-            properties: Vec::new(),
-        };
+    let login_success = LoginSuccess {
+        uuid: id,
+        username: identity.username.clone(),
+    };
+    conn.send(OutgoingPackets::LoginSuccess(login_success))
+        .await?;
 
-        assert_eq!(resp.id_for_client(), "11111111-2222-3333-4444-555555555555");
-    }
+    let (inc_tx, out_rx, client) =
+        Client::new(id, identity.username, textures, peer.addr, peer.version);
+    state
+        .new_client
+        .send(client)
+        .await
+        .map_err(|_| Error::new(ErrorKind::Other, "game disconnected"))?;
+
+    play::handle(conn, state, inc_tx, out_rx).await
 }
@@ -1,9 +1,15 @@
 use super::{packets::*, ServerState};
+use bytes::BytesMut;
 use futures::prelude::*;
 use log::{error, info};
-use std::io;
-use tokio::{codec::Framed, io::AsyncWriteExt, net::TcpStream};
+use std::{io, net::SocketAddr};
+use tokio::{
+    codec::{Framed, FramedParts},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
 
+mod legacy;
 mod login;
 mod play;
 mod status;
@@ -16,6 +22,14 @@ pub enum ConnectionState {
     Login,
 }
 
+/// What is known about the client on the other end of a connection after the
+/// handshake.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Peer {
+    pub addr: SocketAddr,
+    pub version: ProtocolVersion,
+}
+
 pub fn accept(conn: TcpStream, state: ServerState) {
     tokio::spawn(async {
         let res = handle_connection(conn, state).await;
@@ -26,16 +40,26 @@ pub fn accept(conn: TcpStream, state: ServerState) {
     });
 }
 
-async fn handle_connection(conn: TcpStream, state: ServerState) -> io::Result<()> {
+async fn handle_connection(
+    mut conn: TcpStream,
+    state: ServerState,
+) -> io::Result<()> {
     let remote_addr = conn.peer_addr()?;
     info!("accepting connection from {}", remote_addr);
 
-    let mut framed = Framed::new(conn, Coder::new(ConnectionState::Start));
+    // Like vanilla, we treat every connection starting with the ID of the
+    // legacy ping as such, even though it could also be the first byte of a
+    // long handshake.
+    let mut first_byte = [0; 1];
+    conn.read_exact(&mut first_byte).await?;
 
-    let handshake = expect_packet!(framed, Handshake);
-    let mut transport = match handshake.next_state {
-        NextState::Login => login::handle(framed, state).await?,
-        NextState::Status => status::handle(framed, state).await?,
+    let mut transport = if first_byte[0] == legacy::PING_ID {
+        legacy::handle(conn, state).await?
+    } else {
+        let mut parts = FramedParts::new(conn, Coder::new(ConnectionState::Start));
+        parts.read_buf = BytesMut::from(&first_byte[..]);
+
+        handle_handshake(Framed::from_parts(parts), state, remote_addr).await?
     };
 
     let _ = AsyncWriteExt::shutdown(&mut transport).await;
@@ -43,3 +67,59 @@ async fn handle_connection(conn: TcpStream, state: ServerState) -> io::Result<()
 
     Ok(())
 }
+
+async fn handle_handshake(
+    mut framed: Framed<TcpStream, Coder>,
+    state: ServerState,
+    remote_addr: SocketAddr,
+) -> io::Result<TcpStream> {
+    let handshake = expect_packet!(framed, Handshake);
+    let version = handshake.version();
+    if let Some(version) = version {
+        framed.codec_mut().set_version(version);
+    }
+
+    let transport = match (handshake.next_state, version) {
+        (NextState::Login, Some(version)) => {
+            let peer = Peer {
+                addr: remote_addr,
+                version,
+            };
+            login::handle(framed, state, peer).await?
+        }
+        (NextState::Login, None) => {
+            framed.codec_mut().set_state(ConnectionState::Login);
+
+            let latest = ProtocolVersion::LATEST;
+            let reason = if handshake.protocol_version < latest.number() {
+                format!("Outdated client! Please use {}", latest.name())
+            } else {
+                format!("Outdated server! I'm still on {}", latest.name())
+            };
+            disconnect(framed, &reason).await?
+        }
+        // Clients of unsupported versions are told the latest version, which
+        // makes them show the server as incompatible.
+        (NextState::Status, version) => {
+            let version = version.unwrap_or(ProtocolVersion::LATEST);
+            status::handle(framed, state, version).await?
+        }
+    };
+
+    Ok(transport)
+}
+
+/// Tells the client why it is being disconnected and hands back the
+/// underlying stream, so the connection can be shut down gracefully.
+///
+/// The connection must be in the login or play state.
+async fn disconnect<S: AsyncRead + AsyncWrite + Unpin>(
+    mut conn: Framed<S, Coder>,
+    reason: &str,
+) -> io::Result<S> {
+    info!("disconnecting client: {}", reason);
+
+    conn.send(OutgoingPackets::Disconnect(Disconnect::with_text(reason)))
+        .await?;
+    Ok(conn.into_inner())
+}
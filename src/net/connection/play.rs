@@ -1,16 +1,56 @@
 use super::ConnectionState;
-use crate::net::{crypto::CryptStream, packets::*, util::Autoflush, ServerState};
-use futures::channel::mpsc;
-use std::io;
-use tokio::{codec::Framed, net::TcpStream};
+use crate::net::{packets::*, ServerState};
+use futures::{channel::mpsc, pin_mut, prelude::*, select};
+use std::io::{self, Error, ErrorKind};
+use tokio::{
+    codec::Framed,
+    io::{AsyncRead, AsyncWrite},
+};
 
-pub async fn handle(
-    mut conn: Framed<Autoflush<CryptStream<TcpStream>>, Coder>,
+pub async fn handle<S: AsyncRead + AsyncWrite + Unpin>(
+    mut conn: Framed<S, Coder>,
     _state: ServerState,
-    _incoming: mpsc::Sender<IncomingPackets>,
-    _outgoing: mpsc::Receiver<OutgoingPackets>,
-) -> io::Result<TcpStream> {
+    mut incoming: mpsc::Sender<IncomingPackets>,
+    mut outgoing: mpsc::Receiver<OutgoingPackets>,
+) -> io::Result<S> {
     conn.codec_mut().set_state(ConnectionState::Play);
 
-    unimplemented!()
+    let (mut sink, mut stream) = conn.split();
+
+    {
+        // Both loops end successfully when their respective other side goes
+        // away, i. e. the client closes the TCP connection or the game drops
+        // its end of the channels. Whichever finishes first tears down the
+        // connection.
+        let read = async {
+            while let Some(packet) = stream.next().await {
+                if incoming.send(packet?).await.is_err() {
+                    break;
+                }
+            }
+
+            Ok::<_, Error>(())
+        };
+        let write = async {
+            while let Some(packet) = outgoing.next().await {
+                sink.send(packet).await?;
+            }
+
+            Ok::<_, Error>(())
+        };
+
+        let (read, write) = (read.fuse(), write.fuse());
+        pin_mut!(read, write);
+
+        select! {
+            res = read => res?,
+            res = write => res?,
+        }
+    }
+
+    let conn = stream.reunite(sink).map_err(|_| {
+        Error::new(ErrorKind::Other, "failed to reunite connection halves")
+    })?;
+
+    Ok(conn.into_inner())
 }
@@ -7,6 +7,7 @@ use tokio::{codec::Framed, net::TcpStream};
 pub async fn handle(
     mut conn: Framed<TcpStream, Coder>,
     state: ServerState,
+    version: ProtocolVersion,
 ) -> io::Result<TcpStream> {
     conn.codec_mut().set_state(ConnectionState::Status);
 
@@ -15,8 +16,8 @@ pub async fn handle(
     let stats = StatusRequest::send_via(state.status_request)
         .await
         .ok_or_else(|| Error::new(ErrorKind::Other, "game disconnected"))?;
-    conn.send(OutgoingPackets::StatusResponse(stats.into()))
-        .await?;
+    let response = StatusResponse::new(stats, version);
+    conn.send(OutgoingPackets::StatusResponse(response)).await?;
 
     while let Some(Ok(IncomingPackets::Ping(ping))) = conn.next().await {
         conn.send(OutgoingPackets::Ping(Ping { value: ping.value }))
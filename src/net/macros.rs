@@ -45,27 +45,33 @@ macro_rules! derive_async_write {
     };
 }
 
-macro_rules! expect_packet {
+/// Reads the next packet from the connection, expecting it to be of the
+/// given type.
+///
+/// Evaluates to an `io::Result` containing the validated packet.
+macro_rules! read_packet {
     ($conn:expr, $packet:ident) => {
         match $conn.next().await {
             Some(Ok(IncomingPackets::$packet(packet))) => {
                 packet.validate_self().map_err(|e| {
                     ::std::io::Error::new(::std::io::ErrorKind::InvalidData, e)
-                })?
-            }
-            Some(Ok(_)) => {
-                return Err(::std::io::Error::new(
-                    ::std::io::ErrorKind::InvalidData,
-                    "received unexpected packet",
-                ))
-            }
-            Some(Err(e)) => return Err(e),
-            None => {
-                return Err(::std::io::Error::new(
-                    ::std::io::ErrorKind::UnexpectedEof,
-                    "reached end of stream",
-                ))
+                })
             }
+            Some(Ok(_)) => Err(::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidData,
+                "received unexpected packet",
+            )),
+            Some(Err(e)) => Err(e),
+            None => Err(::std::io::Error::new(
+                ::std::io::ErrorKind::UnexpectedEof,
+                "reached end of stream",
+            )),
         }
     };
 }
+
+macro_rules! expect_packet {
+    ($conn:expr, $packet:ident) => {
+        read_packet!($conn, $packet)?
+    };
+}
@@ -0,0 +1,286 @@
+use futures::{future, prelude::*};
+use log::error;
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    io::{self, Error, ErrorKind},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+const HAS_JOINED_PATH: &str = "/session/minecraft/hasJoined";
+const JOIN_PATH: &str = "/session/minecraft/join";
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+
+/// A minimal, in-process stand-in for Mojang's session server.
+///
+/// The server answers `hasJoined` requests for all players that have been
+/// registered through `join`, just like the real session server does for
+/// players that have joined through the launcher. Clients can join through
+/// the `join` endpoint with the access token of an account registered through
+/// `register`. This allows testing the online-mode login without access to
+/// the internet.
+///
+/// Only the query parameters sent by this crate are understood. They are not
+/// URL-decoded, which is fine for valid usernames and server digests.
+///
+/// Outside of this crate's tests, the server is only built with the
+/// `mock-session` feature. It keeps running until the tokio runtime shuts
+/// down.
+#[derive(Clone, Debug)]
+pub struct MockSessionServer {
+    addr: SocketAddr,
+    accounts: Arc<Mutex<HashMap<String, Account>>>,
+    joins: Arc<Mutex<HashMap<String, Join>>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Account {
+    username: String,
+    id: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Join {
+    id: String,
+    server_id: String,
+}
+
+/// The body of a request to the `join` endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JoinRequest {
+    access_token: String,
+    selected_profile: String,
+    server_id: String,
+}
+
+impl MockSessionServer {
+    /// Starts the session server on a random port on the loopback interface.
+    ///
+    /// This must be called from within a tokio runtime.
+    pub fn start() -> io::Result<Self> {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+        let listener = TcpListener::bind(&addr)?;
+        let server = MockSessionServer {
+            addr: listener.local_addr()?,
+            accounts: Arc::new(Mutex::new(HashMap::new())),
+            joins: Arc::new(Mutex::new(HashMap::new())),
+        };
+
+        let handler = server.clone();
+        tokio::spawn(listener.incoming().for_each(move |maybe_conn| {
+            match maybe_conn {
+                Ok(conn) => {
+                    let handler = handler.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handler.serve(conn).await {
+                            error!("error in mock session server: {:?}", e);
+                        }
+                    });
+                }
+                Err(e) => error!("error while accepting TCP connection: {:?}", e),
+            }
+
+            future::ready(())
+        }));
+
+        Ok(server)
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The URL of the `hasJoined` endpoint, for use with
+    /// `MojangAuthenticator::with_session_server`.
+    pub fn has_joined_url(&self) -> String {
+        format!("http://{}{}", self.addr, HAS_JOINED_PATH)
+    }
+
+    /// The URL of the `join` endpoint, for use with
+    /// `MojangJoiner::with_session_server`.
+    pub fn join_url(&self) -> String {
+        format!("http://{}{}", self.addr, JOIN_PATH)
+    }
+
+    /// Registers the account of the player with the given name and (undashed)
+    /// UUID, which can then join servers using the given access token.
+    pub fn register(&self, access_token: &str, username: &str, id: &str) {
+        let account = Account {
+            username: username.to_owned(),
+            id: id.to_owned(),
+        };
+
+        self.accounts
+            .lock()
+            .expect("poisoned lock")
+            .insert(access_token.to_owned(), account);
+    }
+
+    /// Registers the player with the given name and (undashed) UUID as having
+    /// joined the server identified by the given server digest.
+    pub fn join(&self, username: &str, id: &str, server_id: &str) {
+        let join = Join {
+            id: id.to_owned(),
+            server_id: server_id.to_owned(),
+        };
+
+        self.joins
+            .lock()
+            .expect("poisoned lock")
+            .insert(username.to_owned(), join);
+    }
+
+    async fn serve(self, mut conn: TcpStream) -> io::Result<()> {
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+
+        let head_len = loop {
+            if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            } else if request.len() > MAX_REQUEST_LEN {
+                return Err(Error::new(ErrorKind::InvalidData, "request too long"));
+            }
+
+            let nread = conn.read(&mut buf).await?;
+            if nread == 0 {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+
+            request.extend_from_slice(&buf[..nread]);
+        };
+
+        let head = String::from_utf8_lossy(&request[..head_len]).into_owned();
+        let mut lines = head.lines();
+        let (method, target) = {
+            let mut parts = lines.next().unwrap_or("").split(' ');
+            (parts.next().unwrap_or(""), parts.next().unwrap_or(""))
+        };
+        let content_length = lines
+            .filter_map(|line| {
+                let mut header = line.splitn(2, ':');
+                match (header.next(), header.next()) {
+                    (Some(name), Some(value))
+                        if name.eq_ignore_ascii_case("content-length") =>
+                    {
+                        value.trim().parse::<usize>().ok()
+                    }
+                    _ => None,
+                }
+            })
+            .next()
+            .unwrap_or(0);
+        if content_length > MAX_REQUEST_LEN {
+            return Err(Error::new(ErrorKind::InvalidData, "request too long"));
+        }
+
+        let mut body = request.split_off(head_len);
+        while body.len() < content_length {
+            let nread = conn.read(&mut buf).await?;
+            if nread == 0 {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+
+            body.extend_from_slice(&buf[..nread]);
+        }
+
+        let response = match method {
+            // The real session server responds with 204 to unknown players.
+            "GET" => match self.has_joined(target) {
+                Some(body) => response("200 OK", Some(&body)),
+                None => response("204 No Content", None),
+            },
+            "POST" if target == JOIN_PATH => match self.handle_join(&body) {
+                Ok(()) => response("204 No Content", None),
+                Err(body) => response("403 Forbidden", Some(&body)),
+            },
+            _ => response("404 Not Found", None),
+        };
+
+        conn.write_all(response.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Records the join of a registered account.
+    ///
+    /// Returns the error body of the real session server if the access token
+    /// does not belong to the selected profile.
+    fn handle_join(&self, body: &[u8]) -> Result<(), String> {
+        let forbidden = || {
+            json!({
+                "error": "ForbiddenOperationException",
+                "errorMessage": "Invalid token.",
+            })
+            .to_string()
+        };
+
+        let request =
+            serde_json::from_slice::<JoinRequest>(body).map_err(|_| forbidden())?;
+        let account = self
+            .accounts
+            .lock()
+            .expect("poisoned lock")
+            .get(&request.access_token)
+            .cloned()
+            .ok_or_else(forbidden)?;
+        if account.id != request.selected_profile {
+            return Err(forbidden());
+        }
+
+        self.join(&account.username, &account.id, &request.server_id);
+        Ok(())
+    }
+
+    fn has_joined(&self, target: &str) -> Option<String> {
+        let mut parts = target.splitn(2, '?');
+        if parts.next()? != HAS_JOINED_PATH {
+            return None;
+        }
+
+        let (mut username, mut server_id) = (None, None);
+        for pair in parts.next()?.split('&') {
+            let mut kv = pair.splitn(2, '=');
+            match (kv.next(), kv.next()) {
+                (Some("username"), Some(v)) => username = Some(v),
+                (Some("serverId"), Some(v)) => server_id = Some(v),
+                _ => {}
+            }
+        }
+
+        let username = username?;
+        let joins = self.joins.lock().expect("poisoned lock");
+        let join = joins.get(username)?;
+
+        if join.server_id != server_id? {
+            return None;
+        }
+
+        let body = json!({
+            "id": join.id,
+            "name": username,
+            "properties": [],
+        });
+        Some(body.to_string())
+    }
+}
+
+fn response(status: &str, body: Option<&str>) -> String {
+    match body {
+        Some(body) => format!(
+            "HTTP/1.1 {}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body,
+        ),
+        None => format!("HTTP/1.1 {}\r\nConnection: close\r\n\r\n", status),
+    }
+}
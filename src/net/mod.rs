@@ -1,34 +1,47 @@
+use crate::game::world::Uuid;
 use futures::{
     channel::mpsc::{self, Receiver, Sender},
     future::{self, AbortRegistration, Abortable},
     prelude::*,
 };
 use log::error;
-use reqwest::r#async::Client as HttpClient;
-use std::{io, net::SocketAddr};
+use std::{i32, io, net::SocketAddr, sync::Arc};
 use tokio::net::TcpListener;
 
 #[macro_use]
 mod macros;
 
+mod auth;
+pub(crate) mod blocking;
 mod connection;
-mod crypto;
+pub(crate) mod crypto;
+#[cfg(any(test, feature = "mock-session"))]
+mod mock_session;
 mod status_request;
-mod util;
+pub(crate) mod util;
 
 pub mod packets;
-pub use self::status_request::*;
+pub use self::{auth::*, status_request::*};
+#[cfg(any(test, feature = "mock-session"))]
+pub use self::mock_session::MockSessionServer;
 
 #[derive(Debug)]
 pub struct Client {
+    id: Uuid,
     incoming: Receiver<packets::IncomingPackets>,
     outgoing: Sender<packets::OutgoingPackets>,
+    protocol_version: packets::ProtocolVersion,
+    remote_addr: SocketAddr,
+    textures: Option<Property>,
     username: String,
 }
 
 #[derive(Debug)]
 pub struct ServerBuilder {
+    authenticator: Arc<dyn Authenticator>,
     bind_addr: Option<SocketAddr>,
+    compression_threshold: Option<usize>,
+    listener: Option<TcpListener>,
     new_player: Option<Sender<Client>>,
     shutdown: Option<AbortRegistration>,
     status_request: Option<Sender<StatusRequest>>,
@@ -36,7 +49,8 @@ pub struct ServerBuilder {
 
 #[derive(Clone)]
 pub struct ServerState {
-    pub http_client: HttpClient,
+    pub authenticator: Arc<dyn Authenticator>,
+    pub compression_threshold: Option<usize>,
     pub keypair: crypto::Keypair,
     pub new_client: Sender<Client>,
     pub status_request: Sender<StatusRequest>,
@@ -44,7 +58,11 @@ pub struct ServerState {
 
 impl Client {
     pub fn new(
+        id: Uuid,
         username: String,
+        textures: Option<Property>,
+        remote_addr: SocketAddr,
+        protocol_version: packets::ProtocolVersion,
     ) -> (
         Sender<packets::IncomingPackets>,
         Receiver<packets::OutgoingPackets>,
@@ -57,13 +75,22 @@ impl Client {
             inc_tx,
             out_rx,
             Client {
+                id,
                 incoming: inc_rx,
                 outgoing: out_tx,
+                protocol_version,
+                remote_addr,
+                textures,
                 username,
             },
         )
     }
 
+    /// The UUID of the player.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
     pub fn incoming(&mut self) -> &mut Receiver<packets::IncomingPackets> {
         &mut self.incoming
     }
@@ -72,6 +99,23 @@ impl Client {
         &mut self.outgoing
     }
 
+    /// The protocol version the client announced in its handshake.
+    pub fn protocol_version(&self) -> packets::ProtocolVersion {
+        self.protocol_version
+    }
+
+    pub fn remote_addr(&self) -> SocketAddr {
+        self.remote_addr
+    }
+
+    /// The signed `textures` property of the player's profile, if any.
+    ///
+    /// This is sent to other clients in the Player List Item packet so they
+    /// can display the player's skin and cape.
+    pub fn textures(&self) -> Option<&Property> {
+        self.textures.as_ref()
+    }
+
     pub fn username(&self) -> &str {
         &self.username
     }
@@ -80,23 +124,67 @@ impl Client {
 impl ServerBuilder {
     pub fn new() -> Self {
         ServerBuilder {
+            authenticator: Arc::new(MojangAuthenticator::new()),
             bind_addr: None,
+            compression_threshold: None,
+            listener: None,
             new_player: None,
             shutdown: None,
             status_request: None,
         }
     }
 
+    /// Sets how players are authenticated during login.
+    ///
+    /// Defaults to `MojangAuthenticator`, i.e. online mode.
+    pub fn authenticator<A: Authenticator + 'static>(mut self, auth: A) -> Self {
+        self.authenticator = Arc::new(auth);
+        self
+    }
+
     pub fn bind_addr(mut self, addr: SocketAddr) -> Self {
         self.bind_addr = Some(addr);
         self
     }
 
+    /// Enables packet compression for packets of at least `threshold` bytes.
+    ///
+    /// Compression is disabled by default.
+    pub fn compression_threshold(mut self, threshold: usize) -> Self {
+        assert!(
+            threshold <= i32::MAX as usize,
+            "compression threshold must fit into an i32"
+        );
+
+        self.compression_threshold = Some(threshold);
+        self
+    }
+
+    /// Accepts connections on an already bound listener instead of binding
+    /// to `bind_addr`.
+    pub fn listener(mut self, listener: TcpListener) -> Self {
+        self.listener = Some(listener);
+        self
+    }
+
     pub fn new_player(mut self, new_player: Sender<Client>) -> Self {
         self.new_player = Some(new_player);
         self
     }
 
+    /// Sets whether players are authenticated against the session server.
+    ///
+    /// Servers in offline mode neither encrypt the connection nor verify the
+    /// identity of their players. This is a shorthand for setting either a
+    /// `MojangAuthenticator` or an `OfflineAuthenticator`.
+    pub fn online_mode(self, enabled: bool) -> Self {
+        if enabled {
+            self.authenticator(MojangAuthenticator::new())
+        } else {
+            self.authenticator(OfflineAuthenticator)
+        }
+    }
+
     pub fn shutdown_on(mut self, reg: AbortRegistration) -> Self {
         self.shutdown = Some(reg);
         self
@@ -108,7 +196,13 @@ impl ServerBuilder {
     }
 
     pub async fn run(self) -> io::Result<()> {
-        let bind_addr = self.bind_addr.expect("missing bind_addr");
+        let listener = match self.listener {
+            Some(listener) => listener,
+            None => {
+                let bind_addr = self.bind_addr.expect("missing bind_addr");
+                TcpListener::bind(&bind_addr)?
+            }
+        };
         let new_client = self.new_player.expect("missing channel for new players");
         let status_request = self
             .status_request
@@ -116,25 +210,21 @@ impl ServerBuilder {
         let keypair = crypto::Keypair::generate();
 
         let state = ServerState {
-            http_client: HttpClient::new(),
+            authenticator: self.authenticator,
+            compression_threshold: self.compression_threshold,
             keypair,
             new_client,
             status_request,
         };
 
-        let handler_fut =
-            TcpListener::bind(&bind_addr)?
-                .incoming()
-                .for_each(|maybe_conn| {
-                    match maybe_conn {
-                        Ok(conn) => connection::accept(conn, state.clone()),
-                        Err(e) => {
-                            error!("error while accepting TCP connection: {:?}", e)
-                        }
-                    }
-
-                    future::ready(())
-                });
+        let handler_fut = listener.incoming().for_each(|maybe_conn| {
+            match maybe_conn {
+                Ok(conn) => connection::accept(conn, state.clone()),
+                Err(e) => error!("error while accepting TCP connection: {:?}", e),
+            }
+
+            future::ready(())
+        });
 
         if let Some(shutdown) = self.shutdown {
             let _ = Abortable::new(handler_fut, shutdown).await;
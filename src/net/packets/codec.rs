@@ -1,18 +1,76 @@
 use super::*;
 use crate::net::connection::ConnectionState;
 use bytes::{BufMut, Bytes, BytesMut};
-use minecraft_varint::VarReadExt;
-use std::io::{Cursor, Error, ErrorKind};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use minecraft_varint::{var_i32_length, var_usize_length, VarReadExt, VarWriteExt};
+use serde::Serialize;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    io::{Cursor, Error, ErrorKind, Read, Write},
+    marker::PhantomData,
+};
 use tokio::codec::{Decoder, Encoder};
 
+/// The maximum size of a decompressed packet the vanilla server accepts.
+const MAX_DECOMPRESSED_LENGTH: usize = 2_097_152;
+
+/// The role of a coder that serves clients, decoding serverbound packets and
+/// encoding clientbound ones.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ServerSide {}
+
+/// The role of a coder that connects to a server, decoding clientbound packets
+/// and encoding serverbound ones.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
-pub struct Coder {
+pub enum ClientSide {}
+
+/// Frames, compresses and (de)serializes the packets of one connection.
+///
+/// The role decides which direction is decoded: `Coder<ServerSide>`, the
+/// default, reads `IncomingPackets` and writes `OutgoingPackets`, while
+/// `Coder<ClientSide>` does the reverse. Both follow the same states,
+/// versions and compression settings.
+pub struct Coder<R = ServerSide> {
     state: ConnectionState,
+    version: ProtocolVersion,
+    compression_threshold: Option<usize>,
+    role: PhantomData<R>,
 }
 
 impl Coder {
+    /// Creates a server-side coder for the latest supported protocol version.
     pub fn new(state: ConnectionState) -> Self {
-        Coder { state }
+        Self::with_version(state, ProtocolVersion::LATEST)
+    }
+
+    pub fn with_version(state: ConnectionState, version: ProtocolVersion) -> Self {
+        Self::with_role(state, version)
+    }
+}
+
+impl Coder<ClientSide> {
+    /// Creates a client-side coder for the latest supported protocol version.
+    pub fn client(state: ConnectionState) -> Self {
+        Self::client_with_version(state, ProtocolVersion::LATEST)
+    }
+
+    pub fn client_with_version(
+        state: ConnectionState,
+        version: ProtocolVersion,
+    ) -> Self {
+        Self::with_role(state, version)
+    }
+}
+
+impl<R> Coder<R> {
+    fn with_role(state: ConnectionState, version: ProtocolVersion) -> Self {
+        Coder {
+            state,
+            version,
+            compression_threshold: None,
+            role: PhantomData,
+        }
     }
 
     pub fn state(self) -> ConnectionState {
@@ -22,6 +80,32 @@ impl Coder {
     pub fn set_state(&mut self, new_state: ConnectionState) {
         self.state = new_state;
     }
+
+    pub fn version(self) -> ProtocolVersion {
+        self.version
+    }
+
+    /// Switches the coder to the given protocol version.
+    ///
+    /// This only affects the play state, since the packets of all other states
+    /// are the same in all supported versions.
+    pub fn set_version(&mut self, version: ProtocolVersion) {
+        self.version = version;
+    }
+
+    pub fn compression_threshold(self) -> Option<usize> {
+        self.compression_threshold
+    }
+
+    /// Switches the coder to the compressed packet format.
+    ///
+    /// Packets whose ID and contents are at least `threshold` bytes long will
+    /// be zlib-compressed. Smaller packets are sent uncompressed, but still
+    /// using the compressed framing. Passing `None` reverts to the uncompressed
+    /// format.
+    pub fn set_compression_threshold(&mut self, threshold: Option<usize>) {
+        self.compression_threshold = threshold;
+    }
 }
 
 macro_rules! eof_to_none {
@@ -36,49 +120,162 @@ macro_rules! eof_to_none {
     };
 }
 
-macro_rules! parse_table {
-    ($packetid:expr, $contents:expr, $($pid:expr => $type:ident),*) => {
-        match $packetid {
-            $($pid => {
-                use bytes::IntoBuf;
+impl<R> Coder<R> {
+    fn read_chunk(&self, src: &mut BytesMut) -> Result<Option<(i32, Bytes)>, Error> {
+        let (length, length_of_len_field) = {
+            let mut cur = Cursor::new(src.by_ref());
+            let length = eof_to_none!(cur.read_var_len())?;
+
+            (length, cur.position() as usize)
+        };
 
-                let packet = serde_minecraft::read_from(&mut $contents.into_buf())?;
-                $crate::net::packets::IncomingPackets::$type(packet)
-            },)*
-            _ => return Err(
-                ::std::io::Error::new(::std::io::ErrorKind::InvalidData,
-                "unexpected packet ID",
-            ))
+        if src.len() < length_of_len_field + length {
+            return Ok(None);
         }
-    };
-}
 
-impl Coder {
-    fn read_chunk(src: &mut BytesMut) -> Result<Option<(i32, Bytes)>, Error> {
-        let mut cur = Cursor::new(src.by_ref());
+        src.advance(length_of_len_field);
+        let frame = src.split_to(length).freeze();
+
+        let data = match self.compression_threshold {
+            Some(threshold) => Self::decompress(frame, threshold)?,
+            None => frame,
+        };
 
-        let length_with_pid = eof_to_none!(cur.read_var_len())?;
-        let length_of_len_field = cur.position() as usize;
+        let mut cur = Cursor::new(&data);
+        let packet_id = cur.read_var_i32()?;
+        let length_of_pid_field = cur.position() as usize;
 
-        let packet_id = eof_to_none!(cur.read_var_i32())?;
-        let length_of_pid_field = cur.position() as usize - length_of_len_field;
+        Ok(Some((packet_id, data.slice_from(length_of_pid_field))))
+    }
 
-        let total_length = length_with_pid + length_of_len_field;
+    fn decompress(frame: Bytes, threshold: usize) -> Result<Bytes, Error> {
+        let mut cur = Cursor::new(&frame);
+        let data_length = cur.read_var_len()?;
+        let compressed = frame.slice_from(cur.position() as usize);
 
-        if src.len() < total_length {
-            return Ok(None);
+        // A data length of zero marks packets sent uncompressed because they
+        // are smaller than the threshold.
+        if data_length == 0 {
+            return Ok(compressed);
+        } else if data_length < threshold {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "compressed packet below threshold",
+            ));
+        } else if data_length > MAX_DECOMPRESSED_LENGTH {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "decompressed packet too long",
+            ));
+        }
+
+        let mut data = Vec::with_capacity(data_length);
+        ZlibDecoder::new(&compressed[..])
+            .take(data_length as u64 + 1)
+            .read_to_end(&mut data)?;
+
+        if data.len() != data_length {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "decompressed packet length mismatch",
+            ));
+        }
+
+        Ok(data.into())
+    }
+
+    pub(super) fn write_packet<T: Serialize>(
+        &self,
+        packet_id: i32,
+        packet: &T,
+        dst: &mut BytesMut,
+    ) -> Result<(), Error> {
+        let config = play::config(self.version);
+        let data_len =
+            serde_minecraft::serialized_size(packet)? + var_i32_length(packet_id);
+
+        match self.compression_threshold {
+            Some(threshold) if data_len >= threshold => {
+                let mut data = BytesMut::with_capacity(data_len);
+                data.write_var_i32(packet_id)?;
+                config.write_to_no_resize(packet, &mut data)?;
+
+                let mut encoder = ZlibEncoder::new(
+                    Vec::with_capacity(data_len),
+                    Compression::default(),
+                );
+                encoder.write_all(&data)?;
+                let compressed = encoder.finish()?;
+
+                let total_len = var_usize_length(data_len) + compressed.len();
+                dst.reserve(var_usize_length(total_len) + total_len);
+
+                dst.write_var_len(total_len)?;
+                dst.write_var_len(data_len)?;
+                dst.put_slice(&compressed);
+            }
+            Some(_) => {
+                // One byte for the zero data length marking uncompressed data
+                let total_len = 1 + data_len;
+                dst.reserve(var_usize_length(total_len) + total_len);
+
+                dst.write_var_len(total_len)?;
+                dst.write_var_len(0)?;
+                dst.write_var_i32(packet_id)?;
+                config.write_to_no_resize(packet, dst)?;
+            }
+            None => {
+                dst.reserve(var_usize_length(data_len) + data_len);
+
+                dst.write_var_len(data_len)?;
+                dst.write_var_i32(packet_id)?;
+                config.write_to_no_resize(packet, dst)?;
+            }
         }
 
-        let contents_length = length_with_pid - length_of_pid_field;
+        Ok(())
+    }
+}
+
+// Implemented by hand, since derives would require the role to implement the
+// traits as well.
+impl<R> Clone for Coder<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
 
-        src.advance(length_of_len_field + length_of_pid_field);
-        let contents_data = src.split_to(contents_length).freeze();
+impl<R> Copy for Coder<R> {}
 
-        Ok(Some((packet_id, contents_data)))
+impl<R> fmt::Debug for Coder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Coder")
+            .field("state", &self.state)
+            .field("version", &self.version)
+            .field("compression_threshold", &self.compression_threshold)
+            .finish()
     }
 }
 
-impl Decoder for Coder {
+impl<R> PartialEq for Coder<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.state == other.state
+            && self.version == other.version
+            && self.compression_threshold == other.compression_threshold
+    }
+}
+
+impl<R> Eq for Coder<R> {}
+
+impl<R> Hash for Coder<R> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.state.hash(state);
+        self.version.hash(state);
+        self.compression_threshold.hash(state);
+    }
+}
+
+impl Decoder for Coder<ServerSide> {
     type Item = IncomingPackets;
     type Error = Error;
 
@@ -88,26 +285,10 @@ impl Decoder for Coder {
     ) -> Result<Option<Self::Item>, Self::Error> {
         use ConnectionState::*;
 
-        let parsed = match Self::read_chunk(src)? {
+        let parsed = match self.read_chunk(src)? {
             Some((packet_id, data)) => match self.state() {
-                Start => parse_table!(
-                    packet_id,
-                    data,
-                    0 => Handshake
-                ),
-                Login => parse_table!(
-                    packet_id,
-                    data,
-                    0 => LoginStart,
-                    1 => EncryptionResponse
-                ),
-                Play => unimplemented!(),
-                Status => parse_table!(
-                    packet_id,
-                    data,
-                    0 => StatusHandshake,
-                    1 => Ping
-                ),
+                Play => play::decode_serverbound(self.version, packet_id, data)?,
+                state => registered::decode_serverbound(state, packet_id, data)?,
             },
             None => return Ok(None),
         };
@@ -116,61 +297,508 @@ impl Decoder for Coder {
     }
 }
 
-macro_rules! serialize_table {
-    ($item:expr, $dst:expr, $($packet:ident => $packet_id:expr),+) => {
-        match $item {
-            $(OutgoingPackets::$packet(p) => {
-                use minecraft_varint::VarWriteExt;
-                use serde_minecraft;
-
-                let packet_id_len = minecraft_varint::var_i32_length($packet_id);
-                let total_len = serde_minecraft::serialized_size(&p)? + packet_id_len;
-
-                $dst.reserve(total_len);
+impl Encoder for Coder<ServerSide> {
+    type Item = OutgoingPackets;
+    type Error = Error;
 
-                $dst.write_var_len(total_len)?;
-                $dst.write_var_i32($packet_id)?;
-                serde_minecraft::write_to_no_resize(&p, $dst)?;
-            })*,
-            _ => return Err(::std::io::Error::new(
-                ::std::io::ErrorKind::Other,
-                "unexpected packet for state"
-            )),
+    fn encode(
+        &mut self,
+        item: Self::Item,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        match self.state() {
+            ConnectionState::Play => play::encode_clientbound(self, item, dst)?,
+            _ => registered::encode_clientbound(self, item, dst)?,
         }
-    };
+
+        Ok(())
+    }
 }
 
-impl Encoder for Coder {
+impl Decoder for Coder<ClientSide> {
     type Item = OutgoingPackets;
     type Error = Error;
 
+    fn decode(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<Option<Self::Item>, Self::Error> {
+        use ConnectionState::*;
+
+        let parsed = match self.read_chunk(src)? {
+            Some((packet_id, data)) => match self.state() {
+                Play => play::decode_clientbound(self.version, packet_id, data)?,
+                state => registered::decode_clientbound(state, packet_id, data)?,
+            },
+            None => return Ok(None),
+        };
+
+        Ok(Some(parsed))
+    }
+}
+
+impl Encoder for Coder<ClientSide> {
+    type Item = IncomingPackets;
+    type Error = Error;
+
     fn encode(
         &mut self,
         item: Self::Item,
         dst: &mut BytesMut,
     ) -> Result<(), Self::Error> {
         match self.state() {
-            ConnectionState::Start => {
-                return Err(Error::new(
-                    ErrorKind::Other,
-                    "unexpected packet for start state",
-                ));
-            }
-            ConnectionState::Login => serialize_table!(
-                item,
-                dst,
-                Disconnect => 0,
-                EncryptionRequest => 1
-            ),
-            ConnectionState::Play => unimplemented!(),
-            ConnectionState::Status => serialize_table!(
-                item,
-                dst,
-                StatusResponse => 0,
-                Ping => 1
-            ),
+            ConnectionState::Play => play::encode_serverbound(self, item, dst)?,
+            _ => registered::encode_serverbound(self, item, dst)?,
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        chat::{Color, Component},
+        world::{
+            BlockPosition, EntityId, Rotation, Uuid, Vec3x32, Vec3x64, Velocity,
+        },
+    };
+    use serde_minecraft::bounded::BoundedString;
+
+    fn ping_round_trip(threshold: Option<usize>) {
+        let mut coder = Coder::new(ConnectionState::Status);
+        coder.set_compression_threshold(threshold);
+
+        let mut buf = BytesMut::new();
+        let ping = Ping { value: 1_234_567 };
+        coder.encode(OutgoingPackets::Ping(ping), &mut buf).unwrap();
+
+        match coder.decode(&mut buf).unwrap() {
+            Some(IncomingPackets::Ping(decoded)) => assert_eq!(decoded, ping),
+            other => panic!("unexpected decoding result {:?}", other),
+        }
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn round_trip_uncompressed() {
+        ping_round_trip(None);
+    }
+
+    #[test]
+    fn round_trip_below_threshold() {
+        ping_round_trip(Some(256));
+    }
+
+    #[test]
+    fn round_trip_compressed() {
+        ping_round_trip(Some(0));
+    }
+
+    #[test]
+    fn decode_incomplete() {
+        let mut coder = Coder::new(ConnectionState::Status);
+        coder.set_compression_threshold(Some(0));
+
+        let mut buf = BytesMut::new();
+        let ping = Ping { value: 42 };
+        coder.encode(OutgoingPackets::Ping(ping), &mut buf).unwrap();
+
+        let mut partial = BytesMut::from(&buf[..buf.len() - 1]);
+        assert!(coder.decode(&mut partial).unwrap().is_none());
+    }
+
+    #[test]
+    fn decode_trailing_bytes() {
+        let mut coder = Coder::new(ConnectionState::Status);
+
+        // A status handshake has no fields, so the last byte is left over.
+        let mut buf = BytesMut::from(&[2, 0x00, 0xAA][..]);
+        let err = coder.decode(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn registered_packet_in_other_state() {
+        let mut coder = Coder::new(ConnectionState::Login);
+        let mut buf = BytesMut::new();
+        let err = coder
+            .encode(OutgoingPackets::Ping(Ping { value: 0 }), &mut buf)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Other);
+
+        // Ping has ID 1 in the status state, where the login state expects
+        // an encryption response.
+        let mut buf = BytesMut::from(&[9, 0x01, 0, 0, 0, 0, 0, 0, 0, 0][..]);
+        assert!(coder.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn login_success_hyphenated_uuid() {
+        let login_success = OutgoingPackets::LoginSuccess(LoginSuccess {
+            uuid: Uuid(0x853c80ef3c3749fdaa49938b674adae6),
+            username: "jeb_".to_owned(),
+        });
+        let mut coder = Coder::new(ConnectionState::Login);
+        let mut buf = BytesMut::new();
+        coder.encode(login_success, &mut buf).unwrap();
+
+        assert_eq!(&buf[..3], &[43, 2, 36]);
+        assert_eq!(&buf[3..39], &b"853c80ef-3c37-49fd-aa49-938b674adae6"[..]);
+        assert_eq!(&buf[39..], &[4, b'j', b'e', b'b', b'_']);
+    }
+
+    #[test]
+    fn login_disconnect_json() {
+        let reason = Component::text("Bye").color(Color::Red);
+        let disconnect = OutgoingPackets::Disconnect(Disconnect { reason });
+        let mut coder = Coder::new(ConnectionState::Login);
+        let mut buf = BytesMut::new();
+        coder.encode(disconnect, &mut buf).unwrap();
+
+        let json = br#"{"text":"Bye","color":"red"}"#;
+        assert_eq!(&buf[..3], &[2 + json.len() as u8, 0, json.len() as u8]);
+        assert_eq!(&buf[3..], &json[..]);
+    }
+
+    fn encode_play(version: ProtocolVersion, packet: OutgoingPackets) -> BytesMut {
+        let mut coder = Coder::with_version(ConnectionState::Play, version);
+        let mut buf = BytesMut::new();
+        coder.encode(packet, &mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn play_packet_ids_per_version() {
+        let keep_alive =
+            OutgoingPackets::KeepAlive(clientbound::KeepAlive { keep_alive_id: 7 });
+
+        for &(version, id) in &[
+            (ProtocolVersion::V340, 0x1F),
+            (ProtocolVersion::V404, 0x21),
+            (ProtocolVersion::V498, 0x20),
+        ] {
+            let buf = encode_play(version, keep_alive.clone());
+            assert_eq!(&buf[..2], &[9, id], "{:?}", version);
+        }
+    }
+
+    #[test]
+    fn play_layout_per_version() {
+        let difficulty = clientbound::ServerDifficulty {
+            difficulty: clientbound::Difficulty::Hard,
+            locked: Some(true),
+        };
+        let difficulty = OutgoingPackets::ServerDifficulty(difficulty);
+
+        let buf = encode_play(ProtocolVersion::V404, difficulty.clone());
+        assert_eq!(&buf[..], &[2, 0x0D, 3]);
+
+        let buf = encode_play(ProtocolVersion::V498, difficulty);
+        assert_eq!(&buf[..], &[3, 0x0D, 3, 1]);
+    }
+
+    #[test]
+    fn play_plugin_message() {
+        let msg = OutgoingPackets::PluginMessage(clientbound::PluginMessage {
+            channel: "a".to_owned(),
+            data: Bytes::from(&[1, 2, 3][..]),
+        });
+        let buf = encode_play(ProtocolVersion::V404, msg);
+        assert_eq!(&buf[..], &[6, 0x19, 1, b'a', 1, 2, 3]);
+
+        let mut coder =
+            Coder::with_version(ConnectionState::Play, ProtocolVersion::V404);
+        let mut buf = BytesMut::from(&[6, 0x0A, 1, b'a', 1, 2, 3][..]);
+        match coder.decode(&mut buf).unwrap() {
+            Some(IncomingPackets::PluginMessage(msg)) => {
+                assert_eq!(msg.channel, "a");
+                assert_eq!(msg.data, &[1, 2, 3][..]);
+            }
+            other => panic!("unexpected decoding result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn play_entity_properties() {
+        let msg = OutgoingPackets::EntityProperties(clientbound::EntityProperties {
+            entity_id: EntityId(1),
+            properties: vec![clientbound::EntityProperty {
+                key: "a".to_owned(),
+                value: 0.0,
+                modifiers: Vec::new(),
+            }],
+        });
+        let buf = encode_play(ProtocolVersion::V498, msg);
+
+        #[rustfmt::skip]
+        assert_eq!(&buf[..], &[
+            17, 0x58, 1,
+            0, 0, 0, 1,
+            1, b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ][..]);
+    }
+
+    #[test]
+    fn play_block_position_layout() {
+        let block_change = || {
+            OutgoingPackets::BlockChange(clientbound::BlockChange {
+                location: BlockPosition { x: 1, y: 2, z: -1 },
+                block_id: 3,
+            })
+        };
+
+        #[rustfmt::skip]
+        assert_eq!(&encode_play(ProtocolVersion::V404, block_change())[..], &[
+            10, 0x0B,
+            0x00, 0x00, 0x00, 0x40, 0x0B, 0xFF, 0xFF, 0xFF,
+            3,
+        ][..]);
+        #[rustfmt::skip]
+        assert_eq!(&encode_play(ProtocolVersion::V498, block_change())[..], &[
+            10, 0x0B,
+            0x00, 0x00, 0x00, 0x7F, 0xFF, 0xFF, 0xF0, 0x02,
+            3,
+        ][..]);
+    }
+
+    #[test]
+    fn play_chunk_data_nbt() {
+        let chunk_data = || {
+            OutgoingPackets::ChunkData(clientbound::ChunkData {
+                chunk_x: 0,
+                chunk_z: 0,
+                full_chunk: true,
+                primary_bit_mask: 0,
+                heightmaps: Some(nbt::Blob::new()),
+                data: Bytes::new(),
+                block_entities: vec![nbt::Blob::new()],
+            })
+        };
+
+        #[rustfmt::skip]
+        assert_eq!(&encode_play(ProtocolVersion::V404, chunk_data())[..], &[
+            17, 0x22,
+            0, 0, 0, 0, 0, 0, 0, 0, 1, 0,
+            0,
+            1, 10, 0, 0, 0,
+        ][..]);
+        #[rustfmt::skip]
+        assert_eq!(&encode_play(ProtocolVersion::V498, chunk_data())[..], &[
+            21, 0x21,
+            0, 0, 0, 0, 0, 0, 0, 0, 1, 0,
+            10, 0, 0, 0,
+            0,
+            1, 10, 0, 0, 0,
+        ][..]);
+    }
+
+    #[test]
+    fn play_spawn_object_type() {
+        let spawn_object = clientbound::SpawnObject {
+            entity_id: EntityId(1),
+            object_uuid: Uuid(2),
+            object_type: 200,
+            position: Vec3x64 {
+                x: 0.0,
+                y: 64.0,
+                z: 0.0,
+            },
+            rotation: Rotation { pitch: 0, yaw: 0 },
+            data: 0,
+            velocity: Velocity { x: 0, y: 0, z: 0 },
+        };
+
+        // 1.14 sends the object type as VarInt, older versions as a byte.
+        let mut client =
+            Coder::client_with_version(ConnectionState::Play, ProtocolVersion::V498);
+        let mut buf = encode_play(
+            ProtocolVersion::V498,
+            OutgoingPackets::SpawnObject(spawn_object),
+        );
+        match client.decode(&mut buf).unwrap() {
+            Some(OutgoingPackets::SpawnObject(decoded)) => {
+                assert_eq!(decoded, spawn_object);
+            }
+            other => panic!("unexpected decoding result {:?}", other),
+        }
+
+        let mut server =
+            Coder::with_version(ConnectionState::Play, ProtocolVersion::V404);
+        let spawn_object = OutgoingPackets::SpawnObject(spawn_object);
+        assert!(server.encode(spawn_object, &mut BytesMut::new()).is_err());
+    }
+
+    #[test]
+    fn play_packet_missing_in_version() {
+        let use_bed = OutgoingPackets::UseBed(clientbound::UseBed {
+            entity_id: EntityId(1),
+            location: BlockPosition { x: 0, y: 0, z: 0 },
+        });
+        let mut coder =
+            Coder::with_version(ConnectionState::Play, ProtocolVersion::V498);
+
+        assert!(coder.encode(use_bed, &mut BytesMut::new()).is_err());
+    }
+
+    #[test]
+    fn client_login_start() {
+        let mut client = Coder::client(ConnectionState::Login);
+        let mut server = Coder::new(ConnectionState::Login);
+
+        let username = BoundedString::new("jeb_".to_owned()).unwrap();
+        let mut buf = BytesMut::new();
+        client
+            .encode(
+                IncomingPackets::LoginStart(LoginStart { username }),
+                &mut buf,
+            )
+            .unwrap();
+        assert_eq!(&buf[..], &[6, 0x00, 4, b'j', b'e', b'b', b'_']);
+
+        match server.decode(&mut buf).unwrap() {
+            Some(IncomingPackets::LoginStart(login_start)) => {
+                assert_eq!(login_start.username.as_str(), "jeb_");
+            }
+            other => panic!("unexpected decoding result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn client_encryption_request_short_token() {
+        let mut client = Coder::client(ConnectionState::Login);
+
+        // Vanilla servers send a 4 byte verify token.
+        #[rustfmt::skip]
+        let mut buf = BytesMut::from(&[
+            10, 0x01,
+            0,
+            2, 0x30, 0x00,
+            4, 1, 2, 3, 4,
+        ][..]);
+        match client.decode(&mut buf).unwrap() {
+            Some(OutgoingPackets::EncryptionRequest(request)) => {
+                assert_eq!(request.public_key, &[0x30, 0x00][..]);
+                assert_eq!(request.verify_token, vec![1, 2, 3, 4]);
+            }
+            other => panic!("unexpected decoding result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn client_status_response() {
+        let response = StatusResponse {
+            version: "1.14.4".to_owned(),
+            protocol_version: 498,
+            players_max: 20,
+            players_online: 1,
+            description: Component::text("A server"),
+            favicon: Some("iVBORw0KGgo=".to_owned()),
+        };
+        let mut client = Coder::client(ConnectionState::Status);
+        let mut server = Coder::new(ConnectionState::Status);
+
+        let mut buf = BytesMut::new();
+        server
+            .encode(OutgoingPackets::StatusResponse(response.clone()), &mut buf)
+            .unwrap();
+        match client.decode(&mut buf).unwrap() {
+            Some(OutgoingPackets::StatusResponse(decoded)) => {
+                assert_eq!(decoded, response);
+            }
+            other => panic!("unexpected decoding result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn client_play_round_trip() {
+        let join_game = clientbound::JoinGame {
+            entity_id: 1,
+            gamemode: 1,
+            dimension: 0,
+            difficulty: Some(clientbound::Difficulty::Hard),
+            max_players: 20,
+            level_type: "default".to_owned(),
+            view_distance: Some(12),
+            reduced_debug_info: false,
+        };
+        let placement = serverbound::PlayerBlockPlacement {
+            location: BlockPosition { x: 1, y: 2, z: -1 },
+            face: 1,
+            hand: serverbound::Hand::Off,
+            cursor_position: Vec3x32 {
+                x: 0.5,
+                y: 1.0,
+                z: 0.5,
+            },
+            inside_block: Some(true),
+        };
+        let header_and_footer = clientbound::PlayerListHeaderAndFooter {
+            header: Component::text("Welcome").color(Color::Gold),
+            footer: Component::text("Players: ").extra(vec![Component::text("1")]),
+        };
+
+        for &version in &[
+            ProtocolVersion::V340,
+            ProtocolVersion::V404,
+            ProtocolVersion::V498,
+        ] {
+            let mut client =
+                Coder::client_with_version(ConnectionState::Play, version);
+            let mut server = Coder::with_version(ConnectionState::Play, version);
+            client.set_compression_threshold(Some(0));
+            server.set_compression_threshold(Some(0));
+
+            let mut buf = BytesMut::new();
+            server
+                .encode(OutgoingPackets::JoinGame(join_game.clone()), &mut buf)
+                .unwrap();
+            match client.decode(&mut buf).unwrap() {
+                Some(OutgoingPackets::JoinGame(decoded)) => {
+                    // 1.14 dropped the difficulty and added the view distance.
+                    let (difficulty, view_distance) = match version {
+                        ProtocolVersion::V498 => (None, Some(12)),
+                        _ => (Some(clientbound::Difficulty::Hard), None),
+                    };
+                    assert_eq!(decoded.difficulty, difficulty);
+                    assert_eq!(decoded.view_distance, view_distance);
+                    assert_eq!(decoded.level_type, join_game.level_type);
+                }
+                other => panic!("unexpected decoding result {:?}", other),
+            }
+
+            server
+                .encode(
+                    OutgoingPackets::PlayerListHeaderAndFooter(
+                        header_and_footer.clone(),
+                    ),
+                    &mut buf,
+                )
+                .unwrap();
+            match client.decode(&mut buf).unwrap() {
+                Some(OutgoingPackets::PlayerListHeaderAndFooter(decoded)) => {
+                    assert_eq!(decoded, header_and_footer, "{:?}", version);
+                }
+                other => panic!("unexpected decoding result {:?}", other),
+            }
+
+            client
+                .encode(IncomingPackets::PlayerBlockPlacement(placement), &mut buf)
+                .unwrap();
+            match server.decode(&mut buf).unwrap() {
+                Some(IncomingPackets::PlayerBlockPlacement(decoded)) => {
+                    let inside_block = match version {
+                        ProtocolVersion::V498 => placement.inside_block,
+                        _ => None,
+                    };
+                    assert_eq!(decoded.inside_block, inside_block);
+                    assert_eq!(decoded.location, placement.location);
+                    assert_eq!(decoded.hand, placement.hand);
+                }
+                other => panic!("unexpected decoding result {:?}", other),
+            }
+            assert!(buf.is_empty());
+        }
+    }
+}
@@ -1,27 +1,41 @@
-use super::Incoming;
-use serde::Deserialize;
-use serde_repr::Deserialize_repr;
+use super::{Incoming, ProtocolVersion};
+use minecraft_derive::Packet;
+use serde::{Deserialize, Serialize};
+use serde_minecraft::{bounded::BoundedString, max_length};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 
-#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Deserialize_repr)]
+max_length! {
+    /// The maximum length of the server address in the handshake.
+    pub ServerAddrLength = 255;
+}
+
+#[derive(
+    Copy, Clone, Debug, Hash, Eq, PartialEq, Deserialize_repr, Serialize_repr,
+)]
 #[repr(u8)]
 pub enum NextState {
     Status = 1,
     Login = 2,
 }
 
-#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[derive(Clone, Debug, Hash, Eq, Packet, PartialEq, Deserialize, Serialize)]
+#[packet(id = 0x00, state = Start, direction = Serverbound)]
 pub struct Handshake {
     pub protocol_version: i32,
-    pub server_addr: String,
+    pub server_addr: BoundedString<ServerAddrLength>,
     pub server_port: u16,
     pub next_state: NextState,
 }
 
-impl Incoming for Handshake {
-    fn validate(&self) -> Result<(), String> {
-        match self.protocol_version {
-            404 => Ok(()),
-            _ => Err("Unknown protocol version".to_owned()),
-        }
+impl Handshake {
+    /// The protocol version of the client, if it is supported.
+    ///
+    /// This is not checked in `validate`, since clients of other versions
+    /// must still be able to query the status of the server and need to be
+    /// told why they cannot log in.
+    pub fn version(&self) -> Option<ProtocolVersion> {
+        ProtocolVersion::from_number(self.protocol_version)
     }
 }
+
+impl Incoming for Handshake {}
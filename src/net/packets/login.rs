@@ -1,25 +1,37 @@
 use super::Incoming;
+use crate::game::{chat::Component, world::Uuid};
 use bytes::Bytes;
+use minecraft_derive::Packet;
 use openssl::{
     error::ErrorStack,
     rsa::{Padding, Rsa},
 };
-use serde::{Deserialize, Serialize};
-#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_minecraft::{bounded::BoundedString, max_length};
+
+max_length! {
+    /// The maximum length of a username.
+    pub UsernameLength = 16;
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Packet, PartialEq, Serialize)]
+#[packet(id = 0x00, state = Login, direction = Clientbound)]
 pub struct Disconnect {
-    pub reason: String,
+    #[serde(with = "serde_minecraft::json")]
+    pub reason: Component,
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Packet, PartialEq, Serialize)]
+#[packet(id = 0x01, state = Login, direction = Clientbound)]
 pub struct EncryptionRequest {
     pub server_id: String,
     pub public_key: Bytes,
-
-    #[serde(with = "serde_minecraft::array")]
-    pub verify_token: [u8; 16],
+    /// Vanilla servers send 4 bytes, this server sends 16.
+    pub verify_token: Vec<u8>,
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Packet, PartialEq, Serialize)]
+#[packet(id = 0x01, state = Login, direction = Serverbound)]
 pub struct EncryptionResponse {
     pub shared_secret: Vec<u8>,
     pub verify_token: Vec<u8>,
@@ -31,18 +43,68 @@ pub struct DecryptedEncryptionResponse {
     pub verify_token: Vec<u8>,
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Packet, PartialEq, Serialize)]
+#[packet(id = 0x00, state = Login, direction = Serverbound)]
 pub struct LoginStart {
-    pub username: String,
+    pub username: BoundedString<UsernameLength>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Packet, PartialEq, Serialize)]
+#[packet(id = 0x03, state = Login, direction = Clientbound)]
+pub struct SetCompression {
+    pub threshold: i32,
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Packet, PartialEq, Serialize)]
+#[packet(id = 0x02, state = Login, direction = Clientbound)]
 pub struct LoginSuccess {
-    pub uuid: String,
+    /// Sent as hyphenated string.
+    #[serde(
+        deserialize_with = "deserialize_hyphenated",
+        serialize_with = "serialize_hyphenated"
+    )]
+    pub uuid: Uuid,
     pub username: String,
 }
 
+impl Disconnect {
+    /// Creates a disconnect packet with the given plain text as reason.
+    pub fn with_text(reason: &str) -> Self {
+        Disconnect {
+            reason: Component::text(reason),
+        }
+    }
+}
+
 impl EncryptionResponse {
+    /// Encrypts the shared secret and the verify token of the server with the
+    /// public key the server sent in its encryption request.
+    pub fn encrypt_parts(
+        public_key: &[u8],
+        shared_secret: &[u8],
+        verify_token: &[u8],
+    ) -> Result<Self, ErrorStack> {
+        let rsa = Rsa::public_key_from_der(public_key)?;
+
+        let mut shared_secret_buf = vec![0; rsa.size() as usize];
+        let mut verify_token_buf = vec![0; rsa.size() as usize];
+
+        let shared_secret_len = rsa.public_encrypt(
+            shared_secret,
+            &mut shared_secret_buf,
+            Padding::PKCS1,
+        )?;
+        let verify_token_len =
+            rsa.public_encrypt(verify_token, &mut verify_token_buf, Padding::PKCS1)?;
+        shared_secret_buf.truncate(shared_secret_len);
+        verify_token_buf.truncate(verify_token_len);
+
+        Ok(EncryptionResponse {
+            shared_secret: shared_secret_buf,
+            verify_token: verify_token_buf,
+        })
+    }
+
     pub fn decrypt_parts(
         &self,
         private_key: &[u8],
@@ -54,16 +116,18 @@ impl EncryptionResponse {
         let mut verify_token_buf =
             vec![0; (rsa.size() as usize).max(self.verify_token.len())];
 
-        rsa.private_decrypt(
+        let shared_secret_len = rsa.private_decrypt(
             &self.shared_secret,
             &mut shared_secret_buf,
             Padding::PKCS1,
         )?;
-        rsa.private_decrypt(
+        let verify_token_len = rsa.private_decrypt(
             &self.verify_token,
             &mut verify_token_buf,
             Padding::PKCS1,
         )?;
+        shared_secret_buf.truncate(shared_secret_len);
+        verify_token_buf.truncate(verify_token_len);
 
         Ok(DecryptedEncryptionResponse {
             shared_secret: shared_secret_buf,
@@ -96,3 +160,18 @@ impl Incoming for LoginStart {
         }
     }
 }
+
+fn deserialize_hyphenated<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Uuid, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(de::Error::custom)
+}
+
+fn serialize_hyphenated<S: Serializer>(
+    uuid: &Uuid,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(uuid)
+}
@@ -0,0 +1,135 @@
+/// Defines the functions deserializing and serializing the packets of one
+/// direction based on their IDs.
+///
+/// `$decode` deserializes a packet of the `$packets` enum from its ID and
+/// contents with the given config, `$encode` serializes one with its ID.
+///
+/// Packets whose layout differs from the type in `$packets` are sent as the
+/// given wire type (`ID => Type as WireType`), which is converted from and
+/// into the type using `From`.
+///
+/// Packets must consume their whole contents, so that protocol mismatches are
+/// not silently read as garbage. Fields spanning the rest of the packet use
+/// `serde_minecraft::rest`.
+macro_rules! packet_table {
+    (@read $config:ident, $contents:ident) => {
+        $config.read_exact_from_bytes(&$contents)?
+    };
+    (@read $config:ident, $contents:ident, $wire:ty) => {
+        $config.read_exact_from_bytes::<$wire>(&$contents)?.into()
+    };
+    (@convert $packet:ident) => {
+        $packet
+    };
+    (@convert $packet:ident, $wire:ty) => {
+        <$wire>::from($packet)
+    };
+    (
+        $packets:ident, fn $decode:ident, fn $encode:ident;
+        $($pid:expr => $type:ident $(as $wire:ty)?),* $(,)?
+    ) => {
+        pub fn $decode(
+            config: serde_minecraft::Config,
+            packet_id: i32,
+            data: ::bytes::Bytes,
+        ) -> ::std::io::Result<$crate::net::packets::$packets> {
+            let packet = match packet_id {
+                $($pid => {
+                    let packet = packet_table!(@read config, data $(, $wire)?);
+                    $crate::net::packets::$packets::$type(packet)
+                },)*
+                _ => return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidData,
+                    "unexpected packet ID",
+                )),
+            };
+
+            Ok(packet)
+        }
+
+        pub fn $encode<R>(
+            coder: &$crate::net::packets::Coder<R>,
+            packet: $crate::net::packets::$packets,
+            dst: &mut ::bytes::BytesMut,
+        ) -> ::std::io::Result<()> {
+            match packet {
+                $($crate::net::packets::$packets::$type(p) => {
+                    let packet = packet_table!(@convert p $(, $wire)?);
+                    coder.write_packet($pid, &packet, dst)
+                },)*
+                _ => Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::Other,
+                    "unexpected packet for state",
+                )),
+            }
+        }
+    };
+}
+
+/// Defines the functions deserializing and serializing the packets of one
+/// direction in the states with fixed packet IDs, i.e. all but play.
+///
+/// The listed types are looked up by the state and ID they are registered
+/// with for `$direction` through `#[derive(Packet)]`. In turn, deriving
+/// `Packet` for a type missing from the list fails to compile, since the
+/// list implements `Listed` for its types.
+macro_rules! registered_table {
+    (
+        $direction:ident => $packets:ident, fn $decode:ident, fn $encode:ident;
+        $($type:ident),* $(,)?
+    ) => {
+        $(impl $crate::net::packets::Listed<$crate::net::packets::$direction>
+            for $type
+        {
+        })*
+
+        pub fn $decode(
+            state: $crate::net::packets::ConnectionState,
+            packet_id: i32,
+            data: ::bytes::Bytes,
+        ) -> ::std::io::Result<$crate::net::packets::$packets> {
+            let packet = match (state, packet_id) {
+                $((
+                    <$type as $crate::net::packets::Packet<
+                        $crate::net::packets::$direction,
+                    >>::STATE,
+                    <$type as $crate::net::packets::Packet<
+                        $crate::net::packets::$direction,
+                    >>::ID,
+                ) => {
+                    let packet = serde_minecraft::read_exact_from_bytes(&data)?;
+                    $crate::net::packets::$packets::$type(packet)
+                },)*
+                _ => return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidData,
+                    "unexpected packet ID",
+                )),
+            };
+
+            Ok(packet)
+        }
+
+        pub fn $encode<R>(
+            coder: &$crate::net::packets::Coder<R>,
+            packet: $crate::net::packets::$packets,
+            dst: &mut ::bytes::BytesMut,
+        ) -> ::std::io::Result<()> {
+            match packet {
+                $($crate::net::packets::$packets::$type(p)
+                    if coder.state() == <$type as $crate::net::packets::Packet<
+                        $crate::net::packets::$direction,
+                    >>::STATE =>
+                {
+                    let packet_id = <$type as $crate::net::packets::Packet<
+                        $crate::net::packets::$direction,
+                    >>::ID;
+                    coder.write_packet(packet_id, &p, dst)
+                },)*
+                _ => Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::Other,
+                    "unexpected packet for state",
+                )),
+            }
+        }
+    };
+}
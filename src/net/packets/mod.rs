@@ -1,28 +1,177 @@
+use std::marker::PhantomData;
+
+#[macro_use]
+mod macros;
+
 mod codec;
 mod handshake;
 mod login;
+mod play;
+mod registered;
 mod status;
+mod version;
 
-pub use self::{codec::Coder, handshake::*, login::*, status::*};
+pub use self::{
+    codec::{ClientSide, Coder, ServerSide},
+    handshake::*,
+    login::*,
+    play::{clientbound, serverbound},
+    status::*,
+    version::ProtocolVersion,
+};
+pub use crate::net::connection::ConnectionState;
 
 #[derive(Clone, Debug, enum_as_inner::EnumAsInner)]
 pub enum IncomingPackets {
+    Animation(serverbound::Animation),
+    ChatMessage(serverbound::ChatMessage),
+    ClientSettings(serverbound::ClientSettings),
+    ClientStatus(serverbound::ClientStatus),
+    CloseWindow(serverbound::CloseWindow),
+    ConfirmTransaction(serverbound::ConfirmTransaction),
+    CraftRecipeRequest(serverbound::CraftRecipeRequest),
+    EnchantItem(serverbound::EnchantItem),
     EncryptionResponse(EncryptionResponse),
+    EntityAction(serverbound::EntityAction),
     Handshake(Handshake),
+    HeldItemChange(serverbound::HeldItemChange),
+    KeepAlive(serverbound::KeepAlive),
     LoginStart(LoginStart),
+    NameItem(serverbound::NameItem),
+    PickItem(serverbound::PickItem),
     Ping(Ping),
+    Player(serverbound::Player),
+    PlayerAbilities(serverbound::PlayerAbilities),
+    PlayerBlockPlacement(serverbound::PlayerBlockPlacement),
+    PlayerDigging(serverbound::PlayerDigging),
+    PlayerLook(serverbound::PlayerLook),
+    PlayerPosition(serverbound::PlayerPosition),
+    PlayerPositionAndLook(serverbound::PlayerPositionAndLook),
+    PluginMessage(serverbound::PluginMessage),
+    QueryBlockNbt(serverbound::QueryBlockNbt),
+    QueryEntityNbt(serverbound::QueryEntityNbt),
+    ResourcePackStatus(serverbound::ResourcePackStatus),
+    SelectTrade(serverbound::SelectTrade),
+    SetBeaconEffect(serverbound::SetBeaconEffect),
+    Spectate(serverbound::Spectate),
     StatusHandshake(StatusHandshake),
+    SteerBoat(serverbound::SteerBoat),
+    SteerVehicle(serverbound::SteerVehicle),
+    TabComplete(serverbound::TabComplete),
+    TeleportConfirm(serverbound::TeleportConfirm),
+    UpdateCommandBlock(serverbound::UpdateCommandBlock),
+    UpdateCommandBlockMinecart(serverbound::UpdateCommandBlockMinecart),
+    UpdateSign(serverbound::UpdateSign),
+    UpdateStructureBlock(serverbound::UpdateStructureBlock),
+    UseItem(serverbound::UseItem),
+    VehicleMove(serverbound::VehicleMove),
 }
 
 #[derive(Clone, Debug, enum_as_inner::EnumAsInner)]
 pub enum OutgoingPackets {
-    EncryptionRequest(EncryptionRequest),
+    Animation(clientbound::Animation),
+    AttachEntity(clientbound::AttachEntity),
+    BlockAction(clientbound::BlockAction),
+    BlockBreakAnimation(clientbound::BlockBreakAnimation),
+    BlockChange(clientbound::BlockChange),
+    Camera(clientbound::Camera),
+    ChangeGameState(clientbound::ChangeGameState),
+    ChatMessage(clientbound::ChatMessage),
+    ChunkData(clientbound::ChunkData),
+    CloseWindow(clientbound::CloseWindow),
+    CollectItem(clientbound::CollectItem),
+    ConfirmTransaction(clientbound::ConfirmTransaction),
+    CraftRecipeResponse(clientbound::CraftRecipeResponse),
+    DestroyEntities(clientbound::DestroyEntities),
     Disconnect(Disconnect),
+    DisplayScoreboard(clientbound::DisplayScoreboard),
+    Effect(clientbound::Effect),
+    EncryptionRequest(EncryptionRequest),
+    Entity(clientbound::Entity),
+    EntityEffect(clientbound::EntityEffect),
+    EntityHeadLook(clientbound::EntityHeadLook),
+    EntityLook(clientbound::EntityLook),
+    EntityLookAndRelativeMove(clientbound::EntityLookAndRelativeMove),
+    EntityProperties(clientbound::EntityProperties),
+    EntityRelativeMove(clientbound::EntityRelativeMove),
+    EntityStatus(clientbound::EntityStatus),
+    EntityTeleport(clientbound::EntityTeleport),
+    EntityVelocity(clientbound::EntityVelocity),
+    HeldItemChange(clientbound::HeldItemChange),
+    JoinGame(clientbound::JoinGame),
+    KeepAlive(clientbound::KeepAlive),
     LoginSuccess(LoginSuccess),
+    MultiBlockChange(clientbound::MultiBlockChange),
+    NamedSoundEffect(clientbound::NamedSoundEffect),
+    OpenSignEditor(clientbound::OpenSignEditor),
     Ping(Ping),
+    PlayerAbilities(clientbound::PlayerAbilities),
+    PlayerListHeaderAndFooter(clientbound::PlayerListHeaderAndFooter),
+    PlayerPositionAndLook(clientbound::PlayerPositionAndLook),
+    PluginMessage(clientbound::PluginMessage),
+    RemoveEntityEffect(clientbound::RemoveEntityEffect),
+    ResourcePackSend(clientbound::ResourcePackSend),
+    Respawn(clientbound::Respawn),
+    ServerDifficulty(clientbound::ServerDifficulty),
+    SetCompression(SetCompression),
+    SetCooldown(clientbound::SetCooldown),
+    SetExperience(clientbound::SetExperience),
+    SetPassengers(clientbound::SetPassengers),
+    SoundEffect(clientbound::SoundEffect),
+    SpawnExperienceOrb(clientbound::SpawnExperienceOrb),
+    SpawnGlobalEntity(clientbound::SpawnGlobalEntity),
+    SpawnMob(clientbound::SpawnMob),
+    SpawnObject(clientbound::SpawnObject),
+    SpawnPainting(clientbound::SpawnPainting),
+    SpawnPlayer(clientbound::SpawnPlayer),
+    SpawnPosition(clientbound::SpawnPosition),
+    Statistics(clientbound::Statistics),
     StatusResponse(StatusResponse),
+    Tags(clientbound::Tags),
+    TimeUpdate(clientbound::TimeUpdate),
+    UnloadChunk(clientbound::UnloadChunk),
+    UpdateHealth(clientbound::UpdateHealth),
+    UseBed(clientbound::UseBed),
+    VehicleMove(clientbound::VehicleMove),
+    WindowProperty(clientbound::WindowProperty),
 }
 
+/// Marks packets sent from the client to the server.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Serverbound {}
+
+/// Marks packets sent from the server to the client.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Clientbound {}
+
+/// A packet with a fixed ID, sent in direction `D`.
+///
+/// Implemented through `#[derive(Packet)]` for the packets of all states but
+/// play, whose IDs depend on the protocol version.
+pub trait Packet<D>: Listed<D> {
+    /// The state the packet is sent in.
+    const STATE: ConnectionState;
+    /// The ID of the packet in its state.
+    const ID: i32;
+}
+
+/// Implemented for the packets listed in the tables of the codec for
+/// direction `D`.
+///
+/// As a supertrait of `Packet`, it makes deriving `Packet` for a packet that
+/// the codec would never (de)serialize fail to compile.
+pub trait Listed<D> {}
+
+/// The place of a packet in the tables of the codec.
+///
+/// The state and the ID are given as the lengths of the arrays, so that
+/// `#[derive(Packet)]` can implement `Registered` for the slot of a packet.
+/// Two packets taking the same slot then fail to compile.
+pub struct Slot<S, D, I>(PhantomData<(S, D, I)>);
+
+/// Implemented for the slots taken by a packet.
+pub trait Registered {}
+
 pub trait Incoming {
     fn validate(&self) -> Result<(), String> {
         Ok(())
@@ -0,0 +1,606 @@
+use crate::game::{
+    chat::Component,
+    world::{
+        BlockPosition, EntityId, MobType, Rotation, RotationFlipped, Uuid, Vec3x64,
+        Velocity,
+    },
+};
+use bytes::Bytes;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::convert::TryFrom;
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(u8)]
+pub enum ChatPosition {
+    Chat = 0,
+    System = 1,
+    GameInfo = 2,
+}
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(u8)]
+pub enum Difficulty {
+    Peaceful = 0,
+    Easy = 1,
+    Normal = 2,
+    Hard = 3,
+}
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(i8)]
+pub enum ModifierOperation {
+    Add = 0,
+    AddPercentage = 1,
+    Multiply = 2,
+}
+
+/// Entity metadata.
+///
+/// Only the empty set of metadata is supported for now, which is sent as the
+/// terminating index `0xff`. The client fills in default values for all
+/// entries.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Metadata;
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Animation {
+    pub entity_id: EntityId,
+    pub animation: u8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct AttachEntity {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub attached_entity_id: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub holding_entity_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AttributeModifier {
+    pub uuid: Uuid,
+    pub amount: f64,
+    pub operation: ModifierOperation,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct BlockAction {
+    pub location: BlockPosition,
+    pub action_id: u8,
+    pub action_param: u8,
+    pub block_type: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct BlockBreakAnimation {
+    pub entity_id: EntityId,
+    pub location: BlockPosition,
+    pub destroy_stage: i8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct BlockChange {
+    pub location: BlockPosition,
+    pub block_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Camera {
+    pub camera_id: EntityId,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChangeGameState {
+    pub reason: u8,
+    pub value: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ChatMessage {
+    #[serde(with = "serde_minecraft::json")]
+    pub message: Component,
+    pub position: ChatPosition,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChunkData {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub chunk_x: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub chunk_z: i32,
+    pub full_chunk: bool,
+    pub primary_bit_mask: i32,
+    /// Only sent in protocol version 498 and newer, `None` when received in
+    /// older versions. Sent as an empty compound if `None`.
+    #[serde(skip)]
+    pub heightmaps: Option<nbt::Blob>,
+    pub data: Bytes,
+    #[serde(with = "serde_minecraft::nbt::seq")]
+    pub block_entities: Vec<nbt::Blob>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CloseWindow {
+    pub window_id: u8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CollectItem {
+    pub collected_entity_id: EntityId,
+    pub collector_entity_id: EntityId,
+    pub pickup_item_count: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ConfirmTransaction {
+    pub window_id: i8,
+    pub action_number: i16,
+    pub accepted: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CraftRecipeResponse {
+    pub window_id: i8,
+    pub recipe: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct DestroyEntities {
+    pub entity_ids: Vec<EntityId>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct DisplayScoreboard {
+    pub position: i8,
+    pub score_name: String,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Effect {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub effect_id: i32,
+    pub location: BlockPosition,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub data: i32,
+    pub disable_relative_volume: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Entity {
+    pub entity_id: EntityId,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EntityEffect {
+    pub entity_id: EntityId,
+    pub effect_id: i8,
+    pub amplifier: i8,
+    pub duration: i32,
+    pub flags: i8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EntityHeadLook {
+    pub entity_id: EntityId,
+    pub head_yaw: u8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EntityLook {
+    pub entity_id: EntityId,
+    pub rotation: RotationFlipped,
+    pub on_ground: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EntityLookAndRelativeMove {
+    pub entity_id: EntityId,
+    pub delta: Velocity,
+    pub rotation: RotationFlipped,
+    pub on_ground: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EntityProperties {
+    pub entity_id: EntityId,
+    #[serde(with = "serde_minecraft::prefixed::int")]
+    pub properties: Vec<EntityProperty>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EntityProperty {
+    pub key: String,
+    pub value: f64,
+    pub modifiers: Vec<AttributeModifier>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EntityRelativeMove {
+    pub entity_id: EntityId,
+    pub delta: Velocity,
+    pub on_ground: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EntityStatus {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub entity_id: i32,
+    pub entity_status: i8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EntityTeleport {
+    pub entity_id: EntityId,
+    pub position: Vec3x64,
+    pub rotation: RotationFlipped,
+    pub on_ground: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EntityVelocity {
+    pub entity_id: EntityId,
+    pub velocity: Velocity,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct HeldItemChange {
+    pub slot: i8,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct JoinGame {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub entity_id: i32,
+    pub gamemode: u8,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub dimension: i32,
+    /// Only sent before protocol version 498, `None` when received in newer
+    /// versions. Sent as easy, like the vanilla default, if `None`.
+    #[serde(
+        deserialize_with = "deserialize_legacy_difficulty",
+        serialize_with = "serialize_legacy_difficulty"
+    )]
+    pub difficulty: Option<Difficulty>,
+    pub max_players: u8,
+    pub level_type: String,
+    /// Only sent in protocol version 498 and newer, `None` when received in
+    /// older versions. Sent as 10 chunks, like the vanilla default, if `None`.
+    #[serde(skip)]
+    pub view_distance: Option<i32>,
+    pub reduced_debug_info: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct KeepAlive {
+    #[serde(with = "serde_minecraft::fixed_i64")]
+    pub keep_alive_id: i64,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct MultiBlockChange {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub chunk_x: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub chunk_z: i32,
+    pub records: Vec<MultiBlockChangeRecord>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct MultiBlockChangeRecord {
+    pub horizontal_position: u8,
+    pub y_coordinate: u8,
+    pub block_id: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NamedSoundEffect {
+    pub sound_name: String,
+    pub sound_category: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub effect_position_x: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub effect_position_y: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub effect_position_z: i32,
+    pub volume: f32,
+    pub pitch: f32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct OpenSignEditor {
+    pub location: BlockPosition,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayerAbilities {
+    pub flags: i8,
+    pub flying_speed: f32,
+    pub field_of_view_modifier: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PlayerListHeaderAndFooter {
+    #[serde(with = "serde_minecraft::json")]
+    pub header: Component,
+    #[serde(with = "serde_minecraft::json")]
+    pub footer: Component,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayerPositionAndLook {
+    pub position: Vec3x64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub flags: i8,
+    pub teleport_id: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PluginMessage {
+    pub channel: String,
+    #[serde(with = "serde_minecraft::rest")]
+    pub data: Bytes,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RemoveEntityEffect {
+    pub entity_id: EntityId,
+    pub effect_id: i8,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ResourcePackSend {
+    pub url: String,
+    pub hash: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Respawn {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub dimension: i32,
+    /// Like for Join Game, only sent before protocol version 498 and sent as
+    /// easy if `None`.
+    #[serde(
+        deserialize_with = "deserialize_legacy_difficulty",
+        serialize_with = "serialize_legacy_difficulty"
+    )]
+    pub difficulty: Option<Difficulty>,
+    pub gamemode: u8,
+    pub level_type: String,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ServerDifficulty {
+    pub difficulty: Difficulty,
+    /// Only sent in protocol version 498 and newer, `None` when received in
+    /// older versions. Sent as unlocked if `None`.
+    #[serde(skip)]
+    pub locked: Option<bool>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SetCooldown {
+    pub item_id: i32,
+    pub cooldown_ticks: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SetExperience {
+    pub experience_bar: f32,
+    pub level: i32,
+    pub total_experience: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SetPassengers {
+    pub entity_id: EntityId,
+    pub passenger_ids: Vec<EntityId>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SoundEffect {
+    pub sound_id: i32,
+    pub sound_category: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub effect_position_x: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub effect_position_y: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub effect_position_z: i32,
+    pub volume: f32,
+    pub pitch: f32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SpawnExperienceOrb {
+    pub entity_id: EntityId,
+    pub position: Vec3x64,
+    pub count: i16,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SpawnGlobalEntity {
+    pub entity_id: EntityId,
+    pub entity_type: i8,
+    pub position: Vec3x64,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SpawnMob {
+    pub entity_id: EntityId,
+    pub entity_uuid: Uuid,
+    pub mob_type: MobType,
+    pub position: Vec3x64,
+    pub rotation: RotationFlipped,
+    pub head_pitch: u8,
+    pub velocity: Velocity,
+    pub metadata: Metadata,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SpawnObject {
+    pub entity_id: EntityId,
+    pub object_uuid: Uuid,
+    /// Sent as a byte before 1.14, so larger types cannot be sent to older
+    /// clients.
+    #[serde(
+        deserialize_with = "deserialize_object_type",
+        serialize_with = "serialize_object_type"
+    )]
+    pub object_type: i32,
+    pub position: Vec3x64,
+    pub rotation: Rotation,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub data: i32,
+    pub velocity: Velocity,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SpawnPainting {
+    pub entity_id: EntityId,
+    pub entity_uuid: Uuid,
+    pub motive: i32,
+    pub location: BlockPosition,
+    pub direction: i8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SpawnPlayer {
+    pub entity_id: EntityId,
+    pub player_uuid: Uuid,
+    pub position: Vec3x64,
+    pub rotation: RotationFlipped,
+    pub metadata: Metadata,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SpawnPosition {
+    pub location: BlockPosition,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Statistics {
+    pub statistics: Vec<Statistic>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Statistic {
+    pub category_id: i32,
+    pub statistic_id: i32,
+    pub value: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Tag {
+    pub name: String,
+    pub entries: Vec<i32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Tags {
+    pub block_tags: Vec<Tag>,
+    pub item_tags: Vec<Tag>,
+    pub fluid_tags: Vec<Tag>,
+    /// Only sent in protocol version 498 and newer, empty when received in
+    /// older versions.
+    #[serde(skip)]
+    pub entity_tags: Vec<Tag>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct TimeUpdate {
+    #[serde(with = "serde_minecraft::fixed_i64")]
+    pub world_age: i64,
+    #[serde(with = "serde_minecraft::fixed_i64")]
+    pub time_of_day: i64,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UnloadChunk {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub chunk_x: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    pub chunk_z: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct UpdateHealth {
+    pub health: f32,
+    pub food: i32,
+    pub food_saturation: f32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UseBed {
+    pub entity_id: EntityId,
+    pub location: BlockPosition,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct VehicleMove {
+    pub position: Vec3x64,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct WindowProperty {
+    pub window_id: u8,
+    pub property: i16,
+    pub value: i16,
+}
+
+impl<'de> Deserialize<'de> for Metadata {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match u8::deserialize(deserializer)? {
+            0xff => Ok(Metadata),
+            _ => Err(de::Error::custom("unsupported entity metadata")),
+        }
+    }
+}
+
+impl Serialize for Metadata {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(0xff)
+    }
+}
+
+fn deserialize_object_type<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<i32, D::Error> {
+    i8::deserialize(deserializer).map(i32::from)
+}
+
+fn serialize_object_type<S: Serializer>(
+    object_type: &i32,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let object_type = i8::try_from(*object_type).map_err(ser::Error::custom)?;
+    serializer.serialize_i8(object_type)
+}
+
+fn deserialize_legacy_difficulty<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Difficulty>, D::Error> {
+    Difficulty::deserialize(deserializer).map(Some)
+}
+
+fn serialize_legacy_difficulty<S: Serializer>(
+    difficulty: &Option<Difficulty>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    difficulty.unwrap_or(Difficulty::Easy).serialize(serializer)
+}
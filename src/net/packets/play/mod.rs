@@ -0,0 +1,87 @@
+//! Packets of the play state.
+//!
+//! Packets in this state share names between both directions, but differ in
+//! their layout, so they are split by the side that receives them.
+//!
+//! The packet types have the layout of protocol version 404 (1.13.2), plus
+//! the fields added in newer versions. The modules for the individual
+//! versions map them to their packet IDs and convert the packets whose layout
+//! differs. Packets that do not exist in a version cannot be sent to or
+//! received from its clients, nor from its servers when acting as client.
+
+use super::{Coder, IncomingPackets, OutgoingPackets, ProtocolVersion};
+use bytes::{Bytes, BytesMut};
+use serde_minecraft::{position::Layout, Config};
+use std::io;
+
+pub mod clientbound;
+pub mod serverbound;
+
+mod v340;
+mod v404;
+mod v498;
+
+/// Deserializes a serverbound packet sent by a client of the given version.
+pub(super) fn decode_serverbound(
+    version: ProtocolVersion,
+    packet_id: i32,
+    data: Bytes,
+) -> io::Result<IncomingPackets> {
+    let config = config(version);
+    match version {
+        ProtocolVersion::V340 => v340::decode_serverbound(config, packet_id, data),
+        ProtocolVersion::V404 => v404::decode_serverbound(config, packet_id, data),
+        ProtocolVersion::V498 => v498::decode_serverbound(config, packet_id, data),
+    }
+}
+
+/// Serializes a clientbound packet for the version of the given coder.
+pub(super) fn encode_clientbound<R>(
+    coder: &Coder<R>,
+    packet: OutgoingPackets,
+    dst: &mut BytesMut,
+) -> io::Result<()> {
+    match coder.version() {
+        ProtocolVersion::V340 => v340::encode_clientbound(coder, packet, dst),
+        ProtocolVersion::V404 => v404::encode_clientbound(coder, packet, dst),
+        ProtocolVersion::V498 => v498::encode_clientbound(coder, packet, dst),
+    }
+}
+
+/// Deserializes a clientbound packet sent by a server of the given version.
+pub(super) fn decode_clientbound(
+    version: ProtocolVersion,
+    packet_id: i32,
+    data: Bytes,
+) -> io::Result<OutgoingPackets> {
+    let config = config(version);
+    match version {
+        ProtocolVersion::V340 => v340::decode_clientbound(config, packet_id, data),
+        ProtocolVersion::V404 => v404::decode_clientbound(config, packet_id, data),
+        ProtocolVersion::V498 => v498::decode_clientbound(config, packet_id, data),
+    }
+}
+
+/// Serializes a serverbound packet for the version of the given coder.
+pub(super) fn encode_serverbound<R>(
+    coder: &Coder<R>,
+    packet: IncomingPackets,
+    dst: &mut BytesMut,
+) -> io::Result<()> {
+    match coder.version() {
+        ProtocolVersion::V340 => v340::encode_serverbound(coder, packet, dst),
+        ProtocolVersion::V404 => v404::encode_serverbound(coder, packet, dst),
+        ProtocolVersion::V498 => v498::encode_serverbound(coder, packet, dst),
+    }
+}
+
+/// The (de)serializer config for the given version, which differ in the
+/// layout of block positions.
+pub(super) fn config(version: ProtocolVersion) -> Config {
+    let layout = match version {
+        ProtocolVersion::V340 | ProtocolVersion::V404 => Layout::Xyz,
+        ProtocolVersion::V498 => Layout::Xzy,
+    };
+
+    Config::default().layout(layout)
+}
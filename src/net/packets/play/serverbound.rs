@@ -0,0 +1,432 @@
+use crate::{
+    game::world::{BlockPosition, Uuid, Vec3x32, Vec3x64},
+    net::packets::Incoming,
+};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(i32)]
+pub enum ChatMode {
+    Enabled = 0,
+    CommandsOnly = 1,
+    Hidden = 2,
+}
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(i32)]
+pub enum ClientStatusAction {
+    PerformRespawn = 0,
+    RequestStats = 1,
+}
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(i32)]
+pub enum DiggingStatus {
+    StartedDigging = 0,
+    CancelledDigging = 1,
+    FinishedDigging = 2,
+    DropItemStack = 3,
+    DropItem = 4,
+    ShootArrowOrFinishEating = 5,
+    SwapItemInHand = 6,
+}
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(i32)]
+pub enum EntityActionKind {
+    StartSneaking = 0,
+    StopSneaking = 1,
+    LeaveBed = 2,
+    StartSprinting = 3,
+    StopSprinting = 4,
+    StartJumpWithHorse = 5,
+    StopJumpWithHorse = 6,
+    OpenHorseInventory = 7,
+    StartFlyingWithElytra = 8,
+}
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(i32)]
+pub enum Hand {
+    Main = 0,
+    Off = 1,
+}
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(i32)]
+pub enum MainHand {
+    Left = 0,
+    Right = 1,
+}
+
+#[derive(
+    Copy, Clone, Debug, Deserialize_repr, Eq, Hash, PartialEq, Serialize_repr,
+)]
+#[repr(i32)]
+pub enum ResourcePackResult {
+    SuccessfullyLoaded = 0,
+    Declined = 1,
+    FailedDownload = 2,
+    Accepted = 3,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Animation {
+    pub hand: Hand,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ChatMessage {
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ClientSettings {
+    pub locale: String,
+    pub view_distance: i8,
+    pub chat_mode: ChatMode,
+    pub chat_colors: bool,
+    pub displayed_skin_parts: u8,
+    pub main_hand: MainHand,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ClientStatus {
+    pub action: ClientStatusAction,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CloseWindow {
+    pub window_id: u8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ConfirmTransaction {
+    pub window_id: i8,
+    pub action_number: i16,
+    pub accepted: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct CraftRecipeRequest {
+    pub window_id: i8,
+    pub recipe: String,
+    pub make_all: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EnchantItem {
+    pub window_id: i8,
+    pub enchantment: i8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EntityAction {
+    pub entity_id: i32,
+    pub action: EntityActionKind,
+    pub jump_boost: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct HeldItemChange {
+    pub slot: i16,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct KeepAlive {
+    #[serde(with = "serde_minecraft::fixed_i64")]
+    pub keep_alive_id: i64,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct NameItem {
+    pub item_name: String,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PickItem {
+    pub slot_to_use: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Player {
+    pub on_ground: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayerAbilities {
+    pub flags: i8,
+    pub flying_speed: f32,
+    pub walking_speed: f32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayerBlockPlacement {
+    pub location: BlockPosition,
+    pub face: i32,
+    pub hand: Hand,
+    pub cursor_position: Vec3x32,
+    /// Only sent in protocol version 498 and newer, `None` when received in
+    /// older versions. Sent as `false` if `None`.
+    #[serde(skip)]
+    pub inside_block: Option<bool>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PlayerDigging {
+    pub status: DiggingStatus,
+    pub location: BlockPosition,
+    pub face: i8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayerLook {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayerPosition {
+    pub position: Vec3x64,
+    pub on_ground: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayerPositionAndLook {
+    pub position: Vec3x64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PluginMessage {
+    pub channel: String,
+    #[serde(with = "serde_minecraft::rest")]
+    pub data: Bytes,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct QueryBlockNbt {
+    pub transaction_id: i32,
+    pub location: BlockPosition,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct QueryEntityNbt {
+    pub transaction_id: i32,
+    pub entity_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ResourcePackStatus {
+    pub result: ResourcePackResult,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SelectTrade {
+    pub selected_slot: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SetBeaconEffect {
+    pub primary_effect: i32,
+    pub secondary_effect: i32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Spectate {
+    pub target_player: Uuid,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SteerBoat {
+    pub left_paddle_turning: bool,
+    pub right_paddle_turning: bool,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SteerVehicle {
+    pub sideways: f32,
+    pub forward: f32,
+    pub flags: u8,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct TabComplete {
+    pub transaction_id: i32,
+    pub text: String,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct TeleportConfirm {
+    pub teleport_id: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UpdateCommandBlock {
+    pub location: BlockPosition,
+    pub command: String,
+    pub mode: i32,
+    pub flags: i8,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UpdateCommandBlockMinecart {
+    pub entity_id: i32,
+    pub command: String,
+    pub track_output: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UpdateSign {
+    pub location: BlockPosition,
+    pub line_1: String,
+    pub line_2: String,
+    pub line_3: String,
+    pub line_4: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct UpdateStructureBlock {
+    pub location: BlockPosition,
+    pub action: i32,
+    pub mode: i32,
+    pub name: String,
+    pub offset_x: i8,
+    pub offset_y: i8,
+    pub offset_z: i8,
+    pub size_x: i8,
+    pub size_y: i8,
+    pub size_z: i8,
+    pub mirror: i32,
+    pub rotation: i32,
+    pub metadata: String,
+    pub integrity: f32,
+    pub seed: i64,
+    pub flags: i8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UseItem {
+    pub hand: Hand,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct VehicleMove {
+    pub position: Vec3x64,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl Incoming for Animation {}
+
+impl Incoming for ChatMessage {
+    fn validate(&self) -> Result<(), String> {
+        match self.message.chars().count() {
+            0 => Err("empty chat message".to_owned()),
+            1..=256 => Ok(()),
+            _ => Err("chat message too long".to_owned()),
+        }
+    }
+}
+
+impl Incoming for ClientSettings {}
+
+impl Incoming for ClientStatus {}
+
+impl Incoming for CloseWindow {}
+
+impl Incoming for ConfirmTransaction {}
+
+impl Incoming for CraftRecipeRequest {}
+
+impl Incoming for EnchantItem {}
+
+impl Incoming for EntityAction {}
+
+impl Incoming for HeldItemChange {}
+
+impl Incoming for KeepAlive {}
+
+impl Incoming for NameItem {}
+
+impl Incoming for PickItem {}
+
+impl Incoming for Player {}
+
+impl Incoming for PlayerAbilities {}
+
+impl Incoming for PlayerBlockPlacement {}
+
+impl Incoming for PlayerDigging {}
+
+impl Incoming for PlayerLook {}
+
+impl Incoming for PlayerPosition {}
+
+impl Incoming for PlayerPositionAndLook {}
+
+impl Incoming for PluginMessage {}
+
+impl Incoming for QueryBlockNbt {}
+
+impl Incoming for QueryEntityNbt {}
+
+impl Incoming for ResourcePackStatus {}
+
+impl Incoming for SelectTrade {}
+
+impl Incoming for SetBeaconEffect {}
+
+impl Incoming for Spectate {}
+
+impl Incoming for SteerBoat {}
+
+impl Incoming for SteerVehicle {}
+
+impl Incoming for TabComplete {}
+
+impl Incoming for TeleportConfirm {}
+
+impl Incoming for UpdateCommandBlock {}
+
+impl Incoming for UpdateCommandBlockMinecart {}
+
+impl Incoming for UpdateSign {}
+
+impl Incoming for UpdateStructureBlock {}
+
+impl Incoming for UseItem {}
+
+impl Incoming for VehicleMove {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugin_message() {
+        let contents = Bytes::from(&b"\x0fminecraft:brand\x07vanilla"[..]);
+        let msg: PluginMessage =
+            serde_minecraft::read_exact_from_bytes(&contents).unwrap();
+
+        assert_eq!(msg.channel, "minecraft:brand");
+        assert_eq!(msg.data, &b"\x07vanilla"[..]);
+    }
+}
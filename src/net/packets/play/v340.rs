@@ -0,0 +1,97 @@
+//! Packet IDs of the play state for protocol version 340 (1.12.2).
+//!
+//! Packets introduced with 1.13 do not exist in this version, nor do the ones
+//! whose 1.13 layout cannot be expressed in terms of 1.12's. The latter are
+//! Spawn Painting and Statistics, which used names instead of numeric IDs,
+//! and Craft Recipe Request/Response and Tab-Complete, which used numeric
+//! recipe IDs and lacked the transaction ID respectively.
+
+packet_table! {
+    IncomingPackets, fn decode_serverbound, fn encode_serverbound;
+    0x00 => TeleportConfirm,
+    0x02 => ChatMessage,
+    0x03 => ClientStatus,
+    0x04 => ClientSettings,
+    0x05 => ConfirmTransaction,
+    0x06 => EnchantItem,
+    0x08 => CloseWindow,
+    0x09 => PluginMessage,
+    0x0B => KeepAlive,
+    0x0C => Player,
+    0x0D => PlayerPosition,
+    0x0E => PlayerPositionAndLook,
+    0x0F => PlayerLook,
+    0x10 => VehicleMove,
+    0x11 => SteerBoat,
+    0x13 => PlayerAbilities,
+    0x14 => PlayerDigging,
+    0x15 => EntityAction,
+    0x16 => SteerVehicle,
+    0x18 => ResourcePackStatus,
+    0x1A => HeldItemChange,
+    0x1C => UpdateSign,
+    0x1D => Animation,
+    0x1E => Spectate,
+    0x1F => PlayerBlockPlacement,
+    0x20 => UseItem,
+}
+
+packet_table! {
+    OutgoingPackets, fn decode_clientbound, fn encode_clientbound;
+    0x00 => SpawnObject,
+    0x01 => SpawnExperienceOrb,
+    0x02 => SpawnGlobalEntity,
+    0x03 => SpawnMob,
+    0x05 => SpawnPlayer,
+    0x06 => Animation,
+    0x08 => BlockBreakAnimation,
+    0x0A => BlockAction,
+    0x0B => BlockChange,
+    0x0D => ServerDifficulty,
+    0x0F => ChatMessage,
+    0x10 => MultiBlockChange,
+    0x11 => ConfirmTransaction,
+    0x12 => CloseWindow,
+    0x15 => WindowProperty,
+    0x17 => SetCooldown,
+    0x18 => PluginMessage,
+    0x19 => NamedSoundEffect,
+    0x1A => Disconnect,
+    0x1B => EntityStatus,
+    0x1D => UnloadChunk,
+    0x1E => ChangeGameState,
+    0x1F => KeepAlive,
+    0x20 => ChunkData,
+    0x21 => Effect,
+    0x23 => JoinGame,
+    0x25 => Entity,
+    0x26 => EntityRelativeMove,
+    0x27 => EntityLookAndRelativeMove,
+    0x28 => EntityLook,
+    0x29 => VehicleMove,
+    0x2A => OpenSignEditor,
+    0x2C => PlayerAbilities,
+    0x2F => PlayerPositionAndLook,
+    0x30 => UseBed,
+    0x32 => DestroyEntities,
+    0x33 => RemoveEntityEffect,
+    0x34 => ResourcePackSend,
+    0x35 => Respawn,
+    0x36 => EntityHeadLook,
+    0x39 => Camera,
+    0x3A => HeldItemChange,
+    0x3B => DisplayScoreboard,
+    0x3D => AttachEntity,
+    0x3E => EntityVelocity,
+    0x40 => SetExperience,
+    0x41 => UpdateHealth,
+    0x43 => SetPassengers,
+    0x46 => SpawnPosition,
+    0x47 => TimeUpdate,
+    0x49 => SoundEffect,
+    0x4A => PlayerListHeaderAndFooter,
+    0x4B => CollectItem,
+    0x4C => EntityTeleport,
+    0x4E => EntityProperties,
+    0x4F => EntityEffect,
+}
@@ -0,0 +1,109 @@
+//! Packet IDs of the play state for protocol version 404 (1.13.2).
+//!
+//! The packet types are laid out for this version, so no conversions are
+//! necessary.
+
+packet_table! {
+    IncomingPackets, fn decode_serverbound, fn encode_serverbound;
+    0x00 => TeleportConfirm,
+    0x01 => QueryBlockNbt,
+    0x02 => ChatMessage,
+    0x03 => ClientStatus,
+    0x04 => ClientSettings,
+    0x05 => TabComplete,
+    0x06 => ConfirmTransaction,
+    0x07 => EnchantItem,
+    0x09 => CloseWindow,
+    0x0A => PluginMessage,
+    0x0C => QueryEntityNbt,
+    0x0E => KeepAlive,
+    0x0F => Player,
+    0x10 => PlayerPosition,
+    0x11 => PlayerPositionAndLook,
+    0x12 => PlayerLook,
+    0x13 => VehicleMove,
+    0x14 => SteerBoat,
+    0x15 => PickItem,
+    0x16 => CraftRecipeRequest,
+    0x17 => PlayerAbilities,
+    0x18 => PlayerDigging,
+    0x19 => EntityAction,
+    0x1A => SteerVehicle,
+    0x1C => NameItem,
+    0x1D => ResourcePackStatus,
+    0x1F => SelectTrade,
+    0x20 => SetBeaconEffect,
+    0x21 => HeldItemChange,
+    0x22 => UpdateCommandBlock,
+    0x23 => UpdateCommandBlockMinecart,
+    0x25 => UpdateStructureBlock,
+    0x26 => UpdateSign,
+    0x27 => Animation,
+    0x28 => Spectate,
+    0x29 => PlayerBlockPlacement,
+    0x2A => UseItem,
+}
+
+packet_table! {
+    OutgoingPackets, fn decode_clientbound, fn encode_clientbound;
+    0x00 => SpawnObject,
+    0x01 => SpawnExperienceOrb,
+    0x02 => SpawnGlobalEntity,
+    0x03 => SpawnMob,
+    0x04 => SpawnPainting,
+    0x05 => SpawnPlayer,
+    0x06 => Animation,
+    0x07 => Statistics,
+    0x08 => BlockBreakAnimation,
+    0x0A => BlockAction,
+    0x0B => BlockChange,
+    0x0D => ServerDifficulty,
+    0x0E => ChatMessage,
+    0x0F => MultiBlockChange,
+    0x12 => ConfirmTransaction,
+    0x13 => CloseWindow,
+    0x16 => WindowProperty,
+    0x18 => SetCooldown,
+    0x19 => PluginMessage,
+    0x1A => NamedSoundEffect,
+    0x1B => Disconnect,
+    0x1C => EntityStatus,
+    0x1F => UnloadChunk,
+    0x20 => ChangeGameState,
+    0x21 => KeepAlive,
+    0x22 => ChunkData,
+    0x23 => Effect,
+    0x25 => JoinGame,
+    0x27 => Entity,
+    0x28 => EntityRelativeMove,
+    0x29 => EntityLookAndRelativeMove,
+    0x2A => EntityLook,
+    0x2B => VehicleMove,
+    0x2C => OpenSignEditor,
+    0x2D => CraftRecipeResponse,
+    0x2E => PlayerAbilities,
+    0x32 => PlayerPositionAndLook,
+    0x33 => UseBed,
+    0x35 => DestroyEntities,
+    0x36 => RemoveEntityEffect,
+    0x37 => ResourcePackSend,
+    0x38 => Respawn,
+    0x39 => EntityHeadLook,
+    0x3C => Camera,
+    0x3D => HeldItemChange,
+    0x3E => DisplayScoreboard,
+    0x40 => AttachEntity,
+    0x41 => EntityVelocity,
+    0x43 => SetExperience,
+    0x44 => UpdateHealth,
+    0x46 => SetPassengers,
+    0x49 => SpawnPosition,
+    0x4A => TimeUpdate,
+    0x4D => SoundEffect,
+    0x4E => PlayerListHeaderAndFooter,
+    0x4F => CollectItem,
+    0x50 => EntityTeleport,
+    0x52 => EntityProperties,
+    0x53 => EntityEffect,
+    0x55 => Tags,
+}
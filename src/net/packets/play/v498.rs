@@ -0,0 +1,365 @@
+//! Packet IDs and layouts of the play state for protocol version 498 (1.14.4).
+
+use super::{clientbound, serverbound};
+use crate::game::world::{
+    BlockPosition, EntityId, Rotation, Uuid, Vec3x32, Vec3x64, Velocity,
+};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+/// The view distance of the vanilla server, sent if Join Game lacks one.
+const DEFAULT_VIEW_DISTANCE: i32 = 10;
+
+/// Chunk Data gained the heightmaps.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct ChunkData {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    chunk_x: i32,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    chunk_z: i32,
+    full_chunk: bool,
+    primary_bit_mask: i32,
+    #[serde(with = "serde_minecraft::nbt")]
+    heightmaps: nbt::Blob,
+    data: Bytes,
+    #[serde(with = "serde_minecraft::nbt::seq")]
+    block_entities: Vec<nbt::Blob>,
+}
+
+/// Join Game lost the difficulty and gained the view distance.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+struct JoinGame {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    entity_id: i32,
+    gamemode: u8,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    dimension: i32,
+    max_players: u8,
+    level_type: String,
+    view_distance: i32,
+    reduced_debug_info: bool,
+}
+
+/// Player Block Placement moved the hand to the front and gained whether the
+/// player's head is inside a block.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct PlayerBlockPlacement {
+    hand: serverbound::Hand,
+    location: BlockPosition,
+    face: i32,
+    cursor_position: Vec3x32,
+    inside_block: bool,
+}
+
+/// Respawn lost the difficulty.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+struct Respawn {
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    dimension: i32,
+    gamemode: u8,
+    level_type: String,
+}
+
+/// Server Difficulty gained whether the difficulty is locked.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+struct ServerDifficulty {
+    difficulty: clientbound::Difficulty,
+    locked: bool,
+}
+
+/// Spawn Object now sends the object type as VarInt.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct SpawnObject {
+    entity_id: EntityId,
+    object_uuid: Uuid,
+    object_type: i32,
+    position: Vec3x64,
+    rotation: Rotation,
+    #[serde(with = "serde_minecraft::fixed_i32")]
+    data: i32,
+    velocity: Velocity,
+}
+
+/// Tags gained the entity tags.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+struct Tags {
+    block_tags: Vec<clientbound::Tag>,
+    item_tags: Vec<clientbound::Tag>,
+    fluid_tags: Vec<clientbound::Tag>,
+    entity_tags: Vec<clientbound::Tag>,
+}
+
+packet_table! {
+    IncomingPackets, fn decode_serverbound, fn encode_serverbound;
+    0x00 => TeleportConfirm,
+    0x01 => QueryBlockNbt,
+    0x03 => ChatMessage,
+    0x04 => ClientStatus,
+    0x05 => ClientSettings,
+    0x06 => TabComplete,
+    0x07 => ConfirmTransaction,
+    0x08 => EnchantItem,
+    0x0A => CloseWindow,
+    0x0B => PluginMessage,
+    0x0D => QueryEntityNbt,
+    0x0F => KeepAlive,
+    0x11 => PlayerPosition,
+    0x12 => PlayerPositionAndLook,
+    0x13 => PlayerLook,
+    0x14 => Player,
+    0x15 => VehicleMove,
+    0x16 => SteerBoat,
+    0x17 => PickItem,
+    0x18 => CraftRecipeRequest,
+    0x19 => PlayerAbilities,
+    0x1A => PlayerDigging,
+    0x1B => EntityAction,
+    0x1C => SteerVehicle,
+    0x1E => NameItem,
+    0x1F => ResourcePackStatus,
+    0x21 => SelectTrade,
+    0x22 => SetBeaconEffect,
+    0x23 => HeldItemChange,
+    0x24 => UpdateCommandBlock,
+    0x25 => UpdateCommandBlockMinecart,
+    0x28 => UpdateStructureBlock,
+    0x29 => UpdateSign,
+    0x2A => Animation,
+    0x2B => Spectate,
+    0x2C => PlayerBlockPlacement as self::PlayerBlockPlacement,
+    0x2D => UseItem,
+}
+
+packet_table! {
+    OutgoingPackets, fn decode_clientbound, fn encode_clientbound;
+    0x00 => SpawnObject as self::SpawnObject,
+    0x01 => SpawnExperienceOrb,
+    0x02 => SpawnGlobalEntity,
+    0x03 => SpawnMob,
+    0x04 => SpawnPainting,
+    0x05 => SpawnPlayer,
+    0x06 => Animation,
+    0x07 => Statistics,
+    0x08 => BlockBreakAnimation,
+    0x0A => BlockAction,
+    0x0B => BlockChange,
+    0x0D => ServerDifficulty as self::ServerDifficulty,
+    0x0E => ChatMessage,
+    0x0F => MultiBlockChange,
+    0x12 => ConfirmTransaction,
+    0x13 => CloseWindow,
+    0x15 => WindowProperty,
+    0x17 => SetCooldown,
+    0x18 => PluginMessage,
+    0x19 => NamedSoundEffect,
+    0x1A => Disconnect,
+    0x1B => EntityStatus,
+    0x1D => UnloadChunk,
+    0x1E => ChangeGameState,
+    0x20 => KeepAlive,
+    0x21 => ChunkData as self::ChunkData,
+    0x22 => Effect,
+    0x25 => JoinGame as self::JoinGame,
+    0x28 => EntityRelativeMove,
+    0x29 => EntityLookAndRelativeMove,
+    0x2A => EntityLook,
+    0x2B => Entity,
+    0x2C => VehicleMove,
+    0x2F => OpenSignEditor,
+    0x30 => CraftRecipeResponse,
+    0x31 => PlayerAbilities,
+    0x35 => PlayerPositionAndLook,
+    0x37 => DestroyEntities,
+    0x38 => RemoveEntityEffect,
+    0x39 => ResourcePackSend,
+    0x3A => Respawn as self::Respawn,
+    0x3B => EntityHeadLook,
+    0x3E => Camera,
+    0x3F => HeldItemChange,
+    0x42 => DisplayScoreboard,
+    0x44 => AttachEntity,
+    0x45 => EntityVelocity,
+    0x47 => SetExperience,
+    0x48 => UpdateHealth,
+    0x4A => SetPassengers,
+    0x4D => SpawnPosition,
+    0x4E => TimeUpdate,
+    0x51 => SoundEffect,
+    0x53 => PlayerListHeaderAndFooter,
+    0x55 => CollectItem,
+    0x56 => EntityTeleport,
+    0x58 => EntityProperties,
+    0x59 => EntityEffect,
+    0x5B => Tags as self::Tags,
+}
+
+impl From<clientbound::ChunkData> for ChunkData {
+    fn from(packet: clientbound::ChunkData) -> Self {
+        ChunkData {
+            chunk_x: packet.chunk_x,
+            chunk_z: packet.chunk_z,
+            full_chunk: packet.full_chunk,
+            primary_bit_mask: packet.primary_bit_mask,
+            heightmaps: packet.heightmaps.unwrap_or_else(nbt::Blob::new),
+            data: packet.data,
+            block_entities: packet.block_entities,
+        }
+    }
+}
+
+impl From<ChunkData> for clientbound::ChunkData {
+    fn from(packet: ChunkData) -> Self {
+        clientbound::ChunkData {
+            chunk_x: packet.chunk_x,
+            chunk_z: packet.chunk_z,
+            full_chunk: packet.full_chunk,
+            primary_bit_mask: packet.primary_bit_mask,
+            heightmaps: Some(packet.heightmaps),
+            data: packet.data,
+            block_entities: packet.block_entities,
+        }
+    }
+}
+
+impl From<clientbound::JoinGame> for JoinGame {
+    fn from(packet: clientbound::JoinGame) -> Self {
+        JoinGame {
+            entity_id: packet.entity_id,
+            gamemode: packet.gamemode,
+            dimension: packet.dimension,
+            max_players: packet.max_players,
+            level_type: packet.level_type,
+            view_distance: packet.view_distance.unwrap_or(DEFAULT_VIEW_DISTANCE),
+            reduced_debug_info: packet.reduced_debug_info,
+        }
+    }
+}
+
+impl From<JoinGame> for clientbound::JoinGame {
+    /// Clients learn the difficulty from Server Difficulty instead.
+    fn from(packet: JoinGame) -> Self {
+        clientbound::JoinGame {
+            entity_id: packet.entity_id,
+            gamemode: packet.gamemode,
+            dimension: packet.dimension,
+            difficulty: None,
+            max_players: packet.max_players,
+            level_type: packet.level_type,
+            view_distance: Some(packet.view_distance),
+            reduced_debug_info: packet.reduced_debug_info,
+        }
+    }
+}
+
+impl From<PlayerBlockPlacement> for serverbound::PlayerBlockPlacement {
+    fn from(packet: PlayerBlockPlacement) -> Self {
+        serverbound::PlayerBlockPlacement {
+            location: packet.location,
+            face: packet.face,
+            hand: packet.hand,
+            cursor_position: packet.cursor_position,
+            inside_block: Some(packet.inside_block),
+        }
+    }
+}
+
+impl From<serverbound::PlayerBlockPlacement> for PlayerBlockPlacement {
+    fn from(packet: serverbound::PlayerBlockPlacement) -> Self {
+        PlayerBlockPlacement {
+            hand: packet.hand,
+            location: packet.location,
+            face: packet.face,
+            cursor_position: packet.cursor_position,
+            inside_block: packet.inside_block.unwrap_or(false),
+        }
+    }
+}
+
+impl From<clientbound::Respawn> for Respawn {
+    fn from(packet: clientbound::Respawn) -> Self {
+        Respawn {
+            dimension: packet.dimension,
+            gamemode: packet.gamemode,
+            level_type: packet.level_type,
+        }
+    }
+}
+
+impl From<Respawn> for clientbound::Respawn {
+    fn from(packet: Respawn) -> Self {
+        clientbound::Respawn {
+            dimension: packet.dimension,
+            difficulty: None,
+            gamemode: packet.gamemode,
+            level_type: packet.level_type,
+        }
+    }
+}
+
+impl From<clientbound::ServerDifficulty> for ServerDifficulty {
+    fn from(packet: clientbound::ServerDifficulty) -> Self {
+        ServerDifficulty {
+            difficulty: packet.difficulty,
+            locked: packet.locked.unwrap_or(false),
+        }
+    }
+}
+
+impl From<ServerDifficulty> for clientbound::ServerDifficulty {
+    fn from(packet: ServerDifficulty) -> Self {
+        clientbound::ServerDifficulty {
+            difficulty: packet.difficulty,
+            locked: Some(packet.locked),
+        }
+    }
+}
+
+impl From<clientbound::SpawnObject> for SpawnObject {
+    fn from(packet: clientbound::SpawnObject) -> Self {
+        SpawnObject {
+            entity_id: packet.entity_id,
+            object_uuid: packet.object_uuid,
+            object_type: packet.object_type,
+            position: packet.position,
+            rotation: packet.rotation,
+            data: packet.data,
+            velocity: packet.velocity,
+        }
+    }
+}
+
+impl From<SpawnObject> for clientbound::SpawnObject {
+    fn from(packet: SpawnObject) -> Self {
+        clientbound::SpawnObject {
+            entity_id: packet.entity_id,
+            object_uuid: packet.object_uuid,
+            object_type: packet.object_type,
+            position: packet.position,
+            rotation: packet.rotation,
+            data: packet.data,
+            velocity: packet.velocity,
+        }
+    }
+}
+
+impl From<clientbound::Tags> for Tags {
+    fn from(packet: clientbound::Tags) -> Self {
+        Tags {
+            block_tags: packet.block_tags,
+            item_tags: packet.item_tags,
+            fluid_tags: packet.fluid_tags,
+            entity_tags: packet.entity_tags,
+        }
+    }
+}
+
+impl From<Tags> for clientbound::Tags {
+    fn from(packet: Tags) -> Self {
+        clientbound::Tags {
+            block_tags: packet.block_tags,
+            item_tags: packet.item_tags,
+            fluid_tags: packet.fluid_tags,
+            entity_tags: packet.entity_tags,
+        }
+    }
+}
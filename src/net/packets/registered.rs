@@ -0,0 +1,26 @@
+//! The packets of the handshake, status and login states.
+//!
+//! Unlike in play, their IDs are the same in all protocol versions and come
+//! from `#[derive(Packet)]`. Both roles of the codec dispatch through these
+//! tables, so a packet only has to be listed here once per direction.
+
+use super::*;
+
+registered_table! {
+    Serverbound => IncomingPackets, fn decode_serverbound, fn encode_serverbound;
+    EncryptionResponse,
+    Handshake,
+    LoginStart,
+    Ping,
+    StatusHandshake,
+}
+
+registered_table! {
+    Clientbound => OutgoingPackets, fn decode_clientbound, fn encode_clientbound;
+    Disconnect,
+    EncryptionRequest,
+    LoginSuccess,
+    Ping,
+    SetCompression,
+    StatusResponse,
+}
@@ -1,22 +1,33 @@
-use super::Incoming;
-use serde::{Deserialize, Serialize, Serializer};
+use super::{Incoming, ProtocolVersion};
+use crate::game::chat::Component;
+use minecraft_derive::Packet;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{json, Value};
 
-#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+/// The prefix of the favicon in the JSON response.
+const FAVICON_PREFIX: &str = "data:image/png;base64,";
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Packet, PartialEq, Serialize)]
+#[packet(id = 0x01, state = Status, direction = Serverbound)]
+#[packet(id = 0x01, state = Status, direction = Clientbound)]
 pub struct Ping {
     pub value: i64,
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[derive(
+    Copy, Clone, Debug, Default, Deserialize, Eq, Hash, Packet, PartialEq, Serialize,
+)]
+#[packet(id = 0x00, state = Status, direction = Serverbound)]
 pub struct StatusHandshake(());
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Packet, PartialEq)]
+#[packet(id = 0x00, state = Status, direction = Clientbound)]
 pub struct StatusResponse {
     pub version: String,
     pub protocol_version: u32,
     pub players_max: usize,
     pub players_online: usize,
-    pub description: String,
+    pub description: Component,
     pub favicon: Option<String>,
 }
 
@@ -25,7 +36,19 @@ impl Incoming for Ping {}
 impl Incoming for StatusHandshake {}
 
 impl StatusResponse {
-    fn build_json(&self) -> String {
+    /// Creates a status response advertising the given protocol version.
+    pub fn new(stats: crate::net::Status, version: ProtocolVersion) -> Self {
+        StatusResponse {
+            version: version.name().to_owned(),
+            protocol_version: version.number() as u32,
+            players_max: stats.players_max,
+            players_online: stats.players_online,
+            description: stats.description,
+            favicon: stats.favicon,
+        }
+    }
+
+    fn build_json(&self) -> Value {
         let mut json = json!({
             "version": {
                 "name": self.version,
@@ -36,47 +59,107 @@ impl StatusResponse {
                 "online": self.players_online,
                 "sample": []
             },
-            "description": {
-                "text": self.description
-            },
+            "description": self.description,
         });
 
         // Minecraft wants the favicon key to be omitted instead of `null`.
         // Serde serializes `Option::None` to `null` instead of omitting the key,
         // so we need to resort to mutation here.
-        let favicon = self
-            .favicon
-            .as_ref()
-            .map(|i| format!("data:image/png;base64,{}", i));
-        if let Some(icon) = favicon {
-            json.as_object_mut()
-                .expect("wanted object")
-                .insert("favicon".to_owned(), Value::String(icon));
+        if let (Some(icon), Value::Object(map)) = (&self.favicon, &mut json) {
+            let icon = format!("{}{}", FAVICON_PREFIX, icon);
+            map.insert("favicon".to_owned(), Value::String(icon));
         }
 
-        json.to_string()
+        json
     }
 }
 
-impl From<crate::net::Status> for StatusResponse {
-    fn from(stats: crate::net::Status) -> Self {
+impl Serialize for StatusResponse {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serde_minecraft::json::serialize(&self.build_json(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StatusResponse {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Json {
+            version: Version,
+            players: Players,
+            description: Component,
+            favicon: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct Version {
+            name: String,
+            protocol: u32,
+        }
+
+        #[derive(Deserialize)]
+        struct Players {
+            max: usize,
+            online: usize,
+        }
+
+        let json: Json = serde_minecraft::json::deserialize(deserializer)?;
+        let favicon = json.favicon.map(|icon| {
+            if icon.starts_with(FAVICON_PREFIX) {
+                icon[FAVICON_PREFIX.len()..].to_owned()
+            } else {
+                icon
+            }
+        });
+
+        Ok(StatusResponse {
+            version: json.version.name,
+            protocol_version: json.version.protocol,
+            players_max: json.players.max,
+            players_online: json.players.online,
+            description: json.description,
+            favicon,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::chat::Color;
+    use bytes::BytesMut;
+
+    fn response() -> StatusResponse {
         StatusResponse {
             version: "1.13.2".to_owned(),
             protocol_version: 404,
-            players_max: stats.players_max,
-            players_online: stats.players_online,
-            description: stats.description,
-            favicon: stats.favicon,
+            players_max: 20,
+            players_online: 1,
+            description: Component::text("A server").color(Color::Gold),
+            favicon: Some("aWNvbg==".to_owned()),
         }
     }
-}
 
-impl Serialize for StatusResponse {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let string = self.build_json();
-        serializer.serialize_str(&string)
+    #[test]
+    fn round_trip_json() {
+        let json = serde_json::to_value(response()).unwrap();
+        assert_eq!(json["version"]["protocol"], 404);
+        assert_eq!(json["favicon"], "data:image/png;base64,aWNvbg==");
+
+        let parsed: StatusResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, response());
+    }
+
+    #[test]
+    fn round_trip_wire() {
+        let mut buf = BytesMut::new();
+        serde_minecraft::write_to(&response(), &mut buf).unwrap();
+        let parsed: StatusResponse = serde_minecraft::read_from_slice(&buf).unwrap();
+        assert_eq!(parsed, response());
     }
 }
@@ -0,0 +1,70 @@
+/// A version of the protocol supported by the server.
+///
+/// The login and status states are the same in all supported versions, but
+/// the IDs and layouts of the packets of the play state differ. See the
+/// `play` module for how they are mapped.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ProtocolVersion {
+    /// Protocol version 340 (1.12.2).
+    V340,
+    /// Protocol version 404 (1.13.2).
+    V404,
+    /// Protocol version 498 (1.14.4).
+    V498,
+}
+
+impl ProtocolVersion {
+    /// The newest supported version, used for clients of unsupported versions.
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V498;
+
+    /// All supported versions, from oldest to newest.
+    pub const SUPPORTED: [ProtocolVersion; 3] = [
+        ProtocolVersion::V340,
+        ProtocolVersion::V404,
+        ProtocolVersion::V498,
+    ];
+
+    /// Looks up the version with the given number as sent in the handshake.
+    pub fn from_number(number: i32) -> Option<Self> {
+        Self::SUPPORTED
+            .iter()
+            .cloned()
+            .find(|version| version.number() == number)
+    }
+
+    /// The protocol version number.
+    pub fn number(self) -> i32 {
+        match self {
+            ProtocolVersion::V340 => 340,
+            ProtocolVersion::V404 => 404,
+            ProtocolVersion::V498 => 498,
+        }
+    }
+
+    /// The name of the Minecraft release speaking this version.
+    pub fn name(self) -> &'static str {
+        match self {
+            ProtocolVersion::V340 => "1.12.2",
+            ProtocolVersion::V404 => "1.13.2",
+            ProtocolVersion::V498 => "1.14.4",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_number() {
+        for &version in &ProtocolVersion::SUPPORTED {
+            assert_eq!(
+                ProtocolVersion::from_number(version.number()),
+                Some(version)
+            );
+        }
+
+        assert_eq!(ProtocolVersion::from_number(47), None);
+        assert_eq!(ProtocolVersion::from_number(578), None);
+    }
+}
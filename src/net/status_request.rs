@@ -1,3 +1,4 @@
+use crate::game::chat::Component;
 use futures::{
     channel::{mpsc, oneshot},
     prelude::*,
@@ -7,7 +8,7 @@ use futures::{
 pub struct Status {
     pub players_max: usize,
     pub players_online: usize,
-    pub description: String,
+    pub description: Component,
     pub favicon: Option<String>,
 }
 